@@ -0,0 +1,80 @@
+use proptest::prelude::*;
+
+use crate::board::{Diff, DiffType, Pos};
+use crate::pieces::Piece;
+
+/**
+ * any square on the board, all equally likely
+ */
+impl Arbitrary for Pos {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Pos>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (0..8_usize, 0..8_usize)
+            .prop_map(|(x, y)| Pos(x, y))
+            .boxed()
+    }
+}
+
+/**
+ * Any diff that fits in the type, with every square chosen independently,
+ * so most of them are nowhere near legal in any position
+ */
+impl Arbitrary for Diff {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Diff>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let piece = prop_oneof![
+            Just(Piece::King),
+            Just(Piece::Queen),
+            Just(Piece::Rook),
+            Just(Piece::Bishop),
+            Just(Piece::Knight),
+            Just(Piece::Pawn),
+        ];
+
+        let ty = prop_oneof![
+            Just(DiffType::Move),
+            any::<Pos>().prop_map(|cap| DiffType::Capture { cap }),
+            (any::<Pos>(), any::<Pos>())
+                .prop_map(|(rook_from, rook_to)| DiffType::Castle { rook_from, rook_to }),
+            any::<Pos>().prop_map(|captured| DiffType::EnPassant { captured }),
+            (piece, any::<bool>())
+                .prop_map(|(piece, capture)| DiffType::Promote { piece, capture }),
+        ];
+
+        (ty, any::<Pos>(), any::<Pos>())
+            .prop_map(|(ty, from, to)| Diff { ty, from, to })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn apply_never_panics(diff in any::<Diff>()) {
+            let positions = [
+                START_FEN,
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                "8/2P5/8/3pP3/8/8/5p2/k3K3 w - d6 0 1",
+            ];
+
+            for fen in positions.iter() {
+                let mut board = Board::from_fen(fen).unwrap();
+                let _ = board.apply(diff);
+                let _ = board.to_fen();
+            }
+        }
+
+        #[test]
+        fn pos_in_bounds(pos in any::<Pos>()) {
+            prop_assert_eq!(Pos::new(pos.0, pos.1), Ok(pos));
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::board::{Board, Pos, RawBoard};
+use crate::error::ParseError;
+use crate::pieces::{Color, Piece};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+impl Board {
+    /**
+     * Parses a position from an 8x8 diagram, with rank 8 on the first line
+     *
+     * Uppercase letters are white pieces, lowercase letters are black pieces,
+     * and `.` or a space is an empty square. Blank lines are skipped, and white is to move
+     */
+    pub fn from_ascii(diagram: &str) -> Result<Self, ParseError> {
+        let ranks = diagram
+            .lines()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+
+        if ranks.len() != 8 {
+            return Err(ParseError::InvalidRankCount(ranks.len()));
+        }
+
+        let mut board = RawBoard::default();
+
+        for (rank, y) in ranks.into_iter().zip((0..8).rev()) {
+            if rank.chars().count() != 8 {
+                return Err(ParseError::InvalidRankLength(y + 1));
+            }
+
+            for (c, x) in rank.chars().zip(0..8) {
+                if c == '.' || c == ' ' {
+                    continue;
+                }
+
+                let piece =
+                    Piece::from_ident(c.to_ascii_uppercase()).ok_or(ParseError::UnknownPiece(c))?;
+                let color = if c.is_ascii_uppercase() {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+
+                board.set(Pos(x, y), piece, color);
+            }
+        }
+
+        Ok(Board::with(board))
+    }
+
+    /**
+     * Writes the pieces as an 8x8 diagram that `from_ascii` can read back
+     */
+    pub fn to_ascii(&self) -> String {
+        let mut ascii = String::with_capacity(72);
+
+        for y in (0..8).rev() {
+            for x in 0..8 {
+                ascii.push(match self.get(Pos(x, y)) {
+                    Ok((piece, Color::White)) => piece.get_ident(),
+                    Ok((piece, Color::Black)) => piece.get_ident().to_ascii_lowercase(),
+                    Err(_) => '.',
+                });
+            }
+
+            ascii.push('\n');
+        }
+
+        ascii
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn from_ascii_start() {
+        let board = Board::from_ascii(
+            "\
+rnbqkbnr
+pppppppp
+........
+........
+........
+........
+PPPPPPPP
+RNBQKBNR
+",
+        )
+        .unwrap();
+
+        assert!(board == Board::new());
+    }
+
+    #[test]
+    fn ascii_round_trip() {
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+
+        for &fen in &fens {
+            let board = Board::from_fen(fen).unwrap();
+            let ascii = board.to_ascii();
+
+            assert_eq!(Board::from_ascii(&ascii).unwrap().to_ascii(), ascii);
+            assert_eq!(Board::from_ascii(&ascii).unwrap().to_fen(), fen);
+        }
+
+        let spaces = format!("    k   \n{}    K   \n", "        \n".repeat(6));
+        let spaces = Board::from_ascii(&spaces).unwrap();
+
+        assert_eq!(spaces.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    }
+
+    #[test]
+    fn from_ascii_errors() {
+        assert_eq!(
+            Board::from_ascii("........\n").unwrap_err(),
+            ParseError::InvalidRankCount(1)
+        );
+        assert_eq!(
+            Board::from_ascii(&"........\n".repeat(9)).unwrap_err(),
+            ParseError::InvalidRankCount(9)
+        );
+        assert_eq!(
+            Board::from_ascii(&format!(".......\n{}", "........\n".repeat(7))).unwrap_err(),
+            ParseError::InvalidRankLength(8)
+        );
+        assert_eq!(
+            Board::from_ascii(&format!("{}.........\n", "........\n".repeat(7))).unwrap_err(),
+            ParseError::InvalidRankLength(1)
+        );
+        assert_eq!(
+            Board::from_ascii(&format!("...x....\n{}", "........\n".repeat(7))).unwrap_err(),
+            ParseError::UnknownPiece('x')
+        );
+    }
+}
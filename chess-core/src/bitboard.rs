@@ -0,0 +1,183 @@
+use core::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+use crate::board::{Pos, RawBoard};
+use crate::pieces::{Color, Piece};
+
+#[cfg(feature = "bitboard")]
+mod boards;
+
+#[cfg(feature = "bitboard")]
+pub(crate) use boards::Bitboards;
+#[cfg(all(feature = "bitboard", test))]
+pub(crate) use boards::{KING_ATTACKS, KNIGHT_ATTACKS};
+
+/**
+ * A set of squares stored in a `u64`, where bit `y * 8 + x` is set if `Pos(x, y)`
+ * is in the set, the same layout as `RawBoard::occupancy`
+ *
+ * Sets can be combined with `&`, `|`, `^`, and `!`
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Bitboard(pub u64);
+
+/**
+ * the single bit for a square, in the layout used by `Bitboard` and `RawBoard::occupancy`
+ */
+pub(crate) const fn bit(Pos(x, y): Pos) -> u64 {
+    1 << (y * 8 + x)
+}
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0);
+
+    pub fn insert(&mut self, pos: Pos) {
+        self.0 |= bit(pos);
+    }
+
+    pub fn remove(&mut self, pos: Pos) {
+        self.0 &= !bit(pos);
+    }
+
+    pub fn contains(self, pos: Pos) -> bool {
+        self.0 & bit(pos) != 0
+    }
+
+    /**
+     * the number of squares in the set
+     */
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /**
+     * the squares in the set, going across each rank from a1 to h8 like `Pos::all`
+     */
+    pub fn iter(self) -> impl Iterator<Item = Pos> {
+        let mut bits = self.0;
+
+        core::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+
+            let index = bits.trailing_zeros() as usize;
+            bits &= bits - 1;
+            Some(Pos(index % 8, index / 8))
+        })
+    }
+}
+
+impl core::iter::FromIterator<Pos> for Bitboard {
+    fn from_iter<I: IntoIterator<Item = Pos>>(iter: I) -> Self {
+        let mut set = Bitboard::EMPTY;
+
+        for pos in iter {
+            set.insert(pos);
+        }
+
+        set
+    }
+}
+
+macro_rules! impl_bit_op {
+    ($op:ident $op_fun:ident $assign:ident $assign_fun:ident $tok:tt) => {
+        impl $op for Bitboard {
+            type Output = Bitboard;
+
+            fn $op_fun(self, other: Bitboard) -> Bitboard {
+                Bitboard(self.0 $tok other.0)
+            }
+        }
+
+        impl $assign for Bitboard {
+            fn $assign_fun(&mut self, other: Bitboard) {
+                self.0 = self.0 $tok other.0;
+            }
+        }
+    };
+}
+
+impl_bit_op! { BitAnd bitand BitAndAssign bitand_assign & }
+impl_bit_op! { BitOr bitor BitOrAssign bitor_assign | }
+impl_bit_op! { BitXor bitxor BitXorAssign bitxor_assign ^ }
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Bitboard {
+        Bitboard(!self.0)
+    }
+}
+
+impl RawBoard {
+    /**
+     * the squares of every piece of the given type and color,
+     * i.e. `board.bitboard(Piece::Knight, Color::White)` for all of white's knights
+     */
+    pub fn bitboard(&self, piece: Piece, color: Color) -> Bitboard {
+        self.iter()
+            .filter(|&(_, p, c)| p == piece && c == color)
+            .map(|(pos, _, _)| pos)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn insert_remove() {
+        let mut set = Bitboard::EMPTY;
+        assert!(set.is_empty());
+
+        set.insert(Pos::E4);
+        set.insert(Pos::E4);
+        set.insert(Pos::A1);
+        assert!(set.contains(Pos::E4));
+        assert!(!set.contains(Pos::E5));
+        assert_eq!(set.len(), 2);
+        assert_eq!(set, Bitboard(1 | 1 << 28));
+
+        set.remove(Pos::E4);
+        set.remove(Pos::H8);
+        assert_eq!(set.iter().collect::<Vec<_>>(), [Pos::A1]);
+    }
+
+    #[test]
+    fn iter_order() {
+        let set = [Pos::H8, Pos::A2, Pos::H1, Pos::B1]
+            .iter()
+            .copied()
+            .collect::<Bitboard>();
+
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            [Pos::B1, Pos::H1, Pos::A2, Pos::H8]
+        );
+        assert!(Bitboard(!0).iter().eq(Pos::all()));
+    }
+
+    #[test]
+    fn set_ops() {
+        let board = Board::new().board;
+
+        let knights = board.bitboard(Piece::Knight, Color::White);
+        assert_eq!(knights.iter().collect::<Vec<_>>(), [Pos::B1, Pos::G1]);
+
+        let queenside = Pos::all()
+            .filter(|pos| pos.file() < 4)
+            .collect::<Bitboard>();
+        assert_eq!((knights & queenside).iter().collect::<Vec<_>>(), [Pos::B1]);
+        assert_eq!((knights & !queenside).iter().collect::<Vec<_>>(), [Pos::G1]);
+        assert_eq!(knights | queenside, queenside ^ Bitboard(1 << 6));
+
+        let white = Bitboard(board.occupancy_of(Color::White));
+        assert_eq!(knights & white, knights);
+        assert!((board.bitboard(Piece::Pawn, Color::Black) & white).is_empty());
+    }
+}
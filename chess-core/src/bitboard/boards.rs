@@ -0,0 +1,260 @@
+use super::bit;
+use crate::board::{Pos, RawBoard};
+use crate::pieces::{Color, Piece};
+use crate::tables::{KING_DELTAS, KNIGHT_DELTAS};
+
+/**
+ * The pieces stored as one `u64` per piece type per color, where bit `y * 8 + x`
+ * is set if that piece is on `Pos(x, y)`
+ *
+ * `Board` keeps this in sync with its `RawBoard` and uses it for attack queries
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub(crate) struct Bitboards {
+    pieces: [[u64; 6]; 2],
+}
+
+/**
+ * every color and piece type, in the order they are indexed by
+ */
+static COLORS: [Color; 2] = [Color::Black, Color::White];
+static PIECES: [Piece; 6] = [
+    Piece::King,
+    Piece::Queen,
+    Piece::Rook,
+    Piece::Bishop,
+    Piece::Knight,
+    Piece::Pawn,
+];
+
+const ROOK_DIRS: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const BISHOP_DIRS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+
+pub(crate) static KNIGHT_ATTACKS: [u64; 64] = leaper_table(&KNIGHT_DELTAS);
+pub(crate) static KING_ATTACKS: [u64; 64] = leaper_table(&KING_DELTAS);
+
+/**
+ * the squares a pawn of each color would have to stand on to attack a square,
+ * indexed by `Color as usize`
+ */
+static PAWN_ATTACKERS: [[u64; 64]; 2] = [
+    leaper_table(&[(-1, 1), (1, 1)]),
+    leaper_table(&[(-1, -1), (1, -1)]),
+];
+
+/**
+ * for each square, every square that is a single jump away by one of the deltas
+ */
+const fn leaper_table(deltas: &[(i32, i32)]) -> [u64; 64] {
+    let mut table = [0; 64];
+
+    let mut square = 0;
+    while square < 64 {
+        let x = (square % 8) as i32;
+        let y = (square / 8) as i32;
+
+        let mut i = 0;
+        while i < deltas.len() {
+            let (dx, dy) = deltas[i];
+            let (x, y) = (x + dx, y + dy);
+
+            if x >= 0 && x < 8 && y >= 0 && y < 8 {
+                table[square] |= bit(Pos(x as usize, y as usize));
+            }
+
+            i += 1;
+        }
+
+        square += 1;
+    }
+
+    table
+}
+
+impl Bitboards {
+    pub(crate) fn new(board: &RawBoard) -> Self {
+        let mut bitboards = Self::default();
+
+        for (pos, piece, color) in board.iter() {
+            bitboards.pieces[color as usize][piece as usize] |= bit(pos);
+        }
+
+        bitboards
+    }
+
+    /**
+     * copies whatever is on the given square of the board into the bitboards
+     */
+    pub(crate) fn sync(&mut self, board: &RawBoard, pos: Pos) {
+        let bit = bit(pos);
+
+        for color in self.pieces.iter_mut() {
+            for piece in color.iter_mut() {
+                *piece &= !bit;
+            }
+        }
+
+        if let Ok((piece, color)) = board.get(pos) {
+            self.pieces[color as usize][piece as usize] |= bit;
+        }
+    }
+
+    /**
+     * the piece on the square, the board itself is faster to index
+     * so this is only used by `iter` and to cross-check the two
+     */
+    pub(crate) fn get(&self, pos: Pos) -> Option<(Piece, Color)> {
+        let bit = bit(pos);
+
+        COLORS
+            .iter()
+            .flat_map(|&color| PIECES.iter().map(move |&piece| (piece, color)))
+            .find(|&(piece, color)| self.pieces[color as usize][piece as usize] & bit != 0)
+    }
+
+    /**
+     * every piece in the same order as `RawBoard::iter`, across each rank from a1 to h8,
+     * so that the moves generated don't depend on whether the bitboards are enabled
+     */
+    pub(crate) fn iter(&self) -> impl '_ + Iterator<Item = (Pos, Piece, Color)> {
+        let mut bits = self.occupied();
+
+        core::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+
+            let square = bits.trailing_zeros() as usize;
+            bits &= bits - 1;
+
+            let pos = Pos(square % 8, square / 8);
+            let (piece, color) = self.get(pos)?;
+            Some((pos, piece, color))
+        })
+    }
+
+    fn occupied(&self) -> u64 {
+        self.pieces
+            .iter()
+            .flatten()
+            .fold(0, |acc, &bits| acc | bits)
+    }
+
+    /**
+     * checks if any of the attackers is the first piece hit along one of the directions
+     */
+    fn slider_hits(&self, Pos(x, y): Pos, dirs: &[(i32, i32)], attackers: u64) -> bool {
+        let occupied = self.occupied();
+
+        dirs.iter().any(|&(dx, dy)| {
+            let (mut x, mut y) = (x as i32 + dx, y as i32 + dy);
+
+            while (0..8).contains(&x) && (0..8).contains(&y) {
+                let square = bit(Pos(x as usize, y as usize));
+
+                if occupied & square != 0 {
+                    return attackers & square != 0;
+                }
+
+                x += dx;
+                y += dy;
+            }
+
+            false
+        })
+    }
+
+    /**
+     * the same as `Board::is_square_attacked`
+     */
+    pub(crate) fn is_attacked(&self, pos: Pos, by: Color) -> bool {
+        let square = pos.1 * 8 + pos.0;
+        let pieces = &self.pieces[by as usize];
+        let [king, queen, rook, bishop, knight, pawn] = *pieces;
+
+        KNIGHT_ATTACKS[square] & knight != 0
+            || KING_ATTACKS[square] & king != 0
+            || PAWN_ATTACKERS[by as usize][square] & pawn != 0
+            || self.slider_hits(pos, &ROOK_DIRS, rook | queen)
+            || self.slider_hits(pos, &BISHOP_DIRS, bishop | queen)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    /**
+     * walks every line of play to the given depth, checking that the bitboards
+     * agree with the array board at each node, and counts the leaves like perft
+     */
+    fn cross_check(board: &mut Board, depth: u32) -> u64 {
+        assert!(board.bits.iter().eq(board.board.iter()));
+
+        for pos in Pos::all() {
+            assert_eq!(board.bits.get(pos), board.get(pos).ok());
+
+            for &color in &[Color::White, Color::Black] {
+                assert_eq!(
+                    board.bits.is_attacked(pos, color),
+                    board.mailbox_attacked(pos, color),
+                    "{} attacked by {:?} in {}",
+                    pos,
+                    color,
+                    board.to_fen()
+                );
+            }
+        }
+
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = board.all_legal_moves(board.to_move()).collect::<Vec<_>>();
+
+        moves
+            .into_iter()
+            .map(|diff| {
+                let undo = board.apply_with_undo(diff).unwrap();
+                let count = cross_check(board, depth - 1);
+                board.unmake(undo);
+                count
+            })
+            .sum()
+    }
+
+    #[test]
+    fn matches_array_start() {
+        let mut board = Board::new();
+
+        assert_eq!(cross_check(&mut board, 3), 8902);
+        assert_eq!(board.perft(3), 8902);
+    }
+
+    #[test]
+    fn matches_array_kiwipete() {
+        let mut board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+
+        assert_eq!(cross_check(&mut board, 2), 2039);
+    }
+
+    #[test]
+    fn move_order_matches_array() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+
+        let from_array = board
+            .board
+            .iter()
+            .filter(|&(_, _, color)| color == Color::White)
+            .flat_map(|(pos, _, _)| board.get_possible_moves(pos).unwrap())
+            .collect::<Vec<_>>();
+        let moves = board.all_legal_moves(Color::White).collect::<Vec<_>>();
+
+        assert_eq!(moves, from_array);
+        assert_eq!(board.to_uci(moves[0]), "a1b1");
+    }
+}
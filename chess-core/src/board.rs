@@ -1,29 +1,88 @@
+#[cfg(feature = "bitboard")]
+use crate::bitboard::Bitboards;
+use crate::bitboard::{bit, Bitboard};
 use crate::error::*;
 use crate::math::Vector;
 use crate::pieces::{Color, Piece as PieceType, VMove};
+use crate::tables;
+use alloc::string::String;
+use alloc::vec::Vec;
 
 type Piece = (PieceType, Color);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Pos(usize, usize);
+pub struct Pos(pub(crate) usize, pub(crate) usize);
 
+/**
+ * The pieces on each square, with serde this is stored as
+ * 8 ranks from 1 to 8, each of which has 8 files from a to h
+ */
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RawBoard {
     data: [[Option<Piece>; 8]; 8],
 }
 
+/**
+ * Which castling moves are still available to each side,
+ * stored as a bitfield
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CastlingRights(u8);
+
+/**
+ * The files that the king and each castling rook start on, these are
+ * the same for both colors, and only differ from e, h and a in Chess960
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct CastleFiles {
+    pub(crate) king: usize,
+    pub(crate) kingside: usize,
+    pub(crate) queenside: usize,
+}
+
+impl CastleFiles {
+    pub(crate) const STANDARD: Self = Self {
+        king: 4,
+        kingside: 7,
+        queenside: 0,
+    };
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DiffType {
-    Promote { piece: PieceType },
+    Promote { piece: PieceType, capture: bool },
     Capture { cap: Pos },
+    Castle { rook_from: Pos, rook_to: Pos },
+    EnPassant { captured: Pos },
     Move,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Diff {
-    ty: DiffType,
-    from: Pos,
-    to: Pos,
+    pub(crate) ty: DiffType,
+    pub(crate) from: Pos,
+    pub(crate) to: Pos,
+}
+
+/**
+ * Why a move is or isn't legal, see `Board::classify_move`
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveLegality {
+    Legal,
+    /// The move would leave the mover's king in check, or castle out of or through check
+    LeavesKingInCheck,
+    /// The piece belongs to the side that isn't moving
+    NotYourTurn,
+    /// There is no piece on the source square
+    NoPiece,
+    /// The piece moves like that, but something is in the way
+    BlockedPath,
+    /// The piece can't make that move from its square, even on an empty board
+    WrongPattern,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -34,8 +93,28 @@ pub enum GameCondition {
     Mate,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    Ongoing,
+}
+
 impl Pos {
-    pub fn new_unchecked(x: usize, y: usize) -> Self {
+    pub const fn new_unchecked(x: usize, y: usize) -> Self {
+        Self(x, y)
+    }
+
+    /**
+     * like `new`, but usable in constants, i.e. `const E4: Pos = Pos::new_const(4, 3);`
+     *
+     * # Panics
+     *
+     * If the position is outside of the board, which is a compile error in a constant
+     */
+    pub const fn new_const(x: usize, y: usize) -> Self {
+        assert!(x < 8 && y < 8, "position is out of the bounds of the board");
         Self(x, y)
     }
 
@@ -43,7 +122,10 @@ impl Pos {
         if x < 8 && y < 8 {
             Ok(Self(x, y))
         } else {
-            Err(OutOfBounds)
+            Err(OutOfBounds {
+                x: x as i64,
+                y: y as i64,
+            })
         }
     }
 
@@ -51,7 +133,10 @@ impl Pos {
         if x >= 0 && y >= 0 && x < 8 && y < 8 {
             Ok(Self(x as usize, y as usize))
         } else {
-            Err(OutOfBounds)
+            Err(OutOfBounds {
+                x: x.into(),
+                y: y.into(),
+            })
         }
     }
 
@@ -61,37 +146,405 @@ impl Pos {
             y: self.1 as i32,
         }
     }
+
+    /**
+     * the file of the square, from 0 for the a file to 7 for the h file
+     */
+    pub fn file(self) -> usize {
+        self.0
+    }
+
+    /**
+     * the rank of the square, from 0 for the first rank to 7 for the eighth
+     */
+    pub fn rank(self) -> usize {
+        self.1
+    }
+
+    /**
+     * the letter of the square's file, from `a` to `h`
+     */
+    pub fn file_char(self) -> char {
+        (b'a' + self.0 as u8) as char
+    }
+
+    /**
+     * The square `dx` files and `dy` ranks away, towards the h file and the eighth rank,
+     * or `None` if that is off the board
+     */
+    pub fn offset(self, dx: i32, dy: i32) -> Option<Pos> {
+        Pos::try_from(self.into() + Vector { x: dx, y: dy }).ok()
+    }
+
+    /**
+     * checks if this is a light square, a1 is a dark square
+     */
+    pub fn is_light(self) -> bool {
+        (self.0 + self.1) % 2 == 1
+    }
+
+    /**
+     * every square on the board, going across each rank from a1 to h8
+     */
+    pub fn all() -> impl Iterator<Item = Pos> {
+        (0..8).flat_map(|y| (0..8).map(move |x| Pos(x, y)))
+    }
+
+    /**
+     * The squares strictly between `a` and `b`, going from `a` towards `b`,
+     * or `None` if they aren't on the same rank, file, or diagonal
+     *
+     * A square isn't aligned with itself, and neighbouring squares have nothing between them
+     */
+    pub fn between(a: Pos, b: Pos) -> Option<impl Iterator<Item = Pos>> {
+        let diff = b.into() - a.into();
+        let (dx, dy) = (diff.x.abs(), diff.y.abs());
+
+        if diff == Vector::zero() || (dx != 0 && dy != 0 && dx != dy) {
+            return None;
+        }
+
+        let step = diff.step();
+        Some((1..dx.max(dy)).map(move |i| Pos::try_from(a.into() + step * i).unwrap()))
+    }
+}
+
+/**
+ * a constant for every square, named by its file and rank, i.e. `Pos::E4`
+ */
+macro_rules! square_consts {
+    ($($y:literal: $($name:ident)*;)*) => {
+        impl Pos {
+            $(square_consts!(@rank $y, 0, $($name)*);)*
+        }
+    };
+    (@rank $y:expr, $x:expr, $name:ident $($rest:ident)*) => {
+        pub const $name: Pos = Pos($x, $y);
+        square_consts!(@rank $y, $x + 1, $($rest)*);
+    };
+    (@rank $y:expr, $x:expr,) => {};
+}
+
+square_consts! {
+    0: A1 B1 C1 D1 E1 F1 G1 H1;
+    1: A2 B2 C2 D2 E2 F2 G2 H2;
+    2: A3 B3 C3 D3 E3 F3 G3 H3;
+    3: A4 B4 C4 D4 E4 F4 G4 H4;
+    4: A5 B5 C5 D5 E5 F5 G5 H5;
+    5: A6 B6 C6 D6 E6 F6 G6 H6;
+    6: A7 B7 C7 D7 E7 F7 G7 H7;
+    7: A8 B8 C8 D8 E8 F8 G8 H8;
+}
+
+/**
+ * squares are ordered rank by rank, and by file within a rank,
+ * so a1 < b1 < ... < h1 < a2 < ... < h8, the same order as `Pos::all`
+ */
+impl Ord for Pos {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (self.1, self.0).cmp(&(other.1, other.0))
+    }
+}
+
+impl PartialOrd for Pos {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl core::str::FromStr for Pos {
+    type Err = ParsePosError;
+
+    /**
+     * parses a square in algebraic notation, i.e. `e4`
+     */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(file @ 'a'..='h'), Some(rank @ '1'..='8'), None) => Ok(Self(
+                file as usize - 'a' as usize,
+                rank as usize - '1' as usize,
+            )),
+            _ => Err(ParsePosError),
+        }
+    }
+}
+
+impl Diff {
+    /**
+     * the square the moving piece starts on
+     */
+    pub fn from(&self) -> Pos {
+        self.from
+    }
+
+    /**
+     * the square the moving piece ends up on
+     */
+    pub fn to(&self) -> Pos {
+        self.to
+    }
+
+    pub fn kind(&self) -> DiffType {
+        self.ty
+    }
+
+    /**
+     * does this diff take a piece, including en passant and capturing promotions
+     */
+    pub fn is_capture(&self) -> bool {
+        match self.ty {
+            DiffType::Capture { .. } | DiffType::EnPassant { .. } => true,
+            DiffType::Promote { capture, .. } => capture,
+            DiffType::Castle { .. } | DiffType::Move => false,
+        }
+    }
+
+    pub fn is_promotion(&self) -> bool {
+        matches!(self.ty, DiffType::Promote { .. })
+    }
+}
+
+/**
+ * with serde, a Pos is stored as a square in algebraic notation, i.e. `e4`
+ */
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::{Pos, String};
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    impl Serialize for Pos {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Pos {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let s = String::deserialize(deserializer)?;
+
+            s.parse()
+                .map_err(|_| de::Error::invalid_value(de::Unexpected::Str(&s), &"a square like e4"))
+        }
+    }
+}
+
+impl CastlingRights {
+    pub const WHITE_KINGSIDE: Self = Self(0b0001);
+    pub const WHITE_QUEENSIDE: Self = Self(0b0010);
+    pub const BLACK_KINGSIDE: Self = Self(0b0100);
+    pub const BLACK_QUEENSIDE: Self = Self(0b1000);
+    pub const ALL: Self = Self(0b1111);
+
+    pub fn none() -> Self {
+        Self(0)
+    }
+
+    pub fn kingside(color: Color) -> Self {
+        match color {
+            Color::White => Self::WHITE_KINGSIDE,
+            Color::Black => Self::BLACK_KINGSIDE,
+        }
+    }
+
+    pub fn queenside(color: Color) -> Self {
+        match color {
+            Color::White => Self::WHITE_QUEENSIDE,
+            Color::Black => Self::BLACK_QUEENSIDE,
+        }
+    }
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    /**
+     * infers the castling rights from the pieces that are still on their home squares
+     */
+    fn infer(board: &RawBoard) -> Self {
+        let mut rights = Self::none();
+
+        for &color in &[Color::White, Color::Black] {
+            let row = back_row(color);
+            let is = |x, pt| board.get(Pos(x, row)).ok() == Some((pt, color));
+
+            if is(4, PieceType::King) {
+                if is(7, PieceType::Rook) {
+                    rights.insert(Self::kingside(color));
+                }
+
+                if is(0, PieceType::Rook) {
+                    rights.insert(Self::queenside(color));
+                }
+            }
+        }
+
+        rights
+    }
+
+    /**
+     * the rights that are lost when a piece moves off of the given square,
+     * or when a piece moves onto it (capturing whatever was there)
+     */
+    fn revoked_by(files: CastleFiles, Pos(x, y): Pos) -> Self {
+        let color = match y {
+            0 => Color::White,
+            7 => Color::Black,
+            _ => return Self::none(),
+        };
+
+        if x == files.king {
+            Self(Self::kingside(color).0 | Self::queenside(color).0)
+        } else if x == files.kingside {
+            Self::kingside(color)
+        } else if x == files.queenside {
+            Self::queenside(color)
+        } else {
+            Self::none()
+        }
+    }
+}
+
+/**
+ * the pieces that a pawn may be promoted to
+ */
+const PROMOTIONS: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+];
+
+/**
+ * the row that the given color's pieces start on, 0 for white and 7 for black
+ */
+fn back_row(color: Color) -> usize {
+    ((1 - color.dir()) / 2 * 7) as usize
 }
 
 impl RawBoard {
-    fn set(&mut self, Pos(x, y): Pos, piece: PieceType, color: Color) {
+    pub(crate) fn set(&mut self, Pos(x, y): Pos, piece: PieceType, color: Color) {
         self.data[y][x] = Some((piece, color));
     }
 
     fn replace(&mut self, Pos(x, y): Pos, piece: Option<Piece>) -> Option<Piece> {
-        std::mem::replace(&mut self.data[y][x], piece)
+        core::mem::replace(&mut self.data[y][x], piece)
     }
 
     fn remove(&mut self, Pos(x, y): Pos) -> Option<Piece> {
         self.data[y][x].take()
     }
 
-    fn get(&self, Pos(x, y): Pos) -> Result<Piece, Error> {
-        self.data[y][x].ok_or(Error::NoPiece)
+    pub(crate) fn get(&self, pos: Pos) -> Result<Piece, Error> {
+        let Pos(x, y) = pos;
+        self.data[y][x].ok_or(Error::NoPiece(pos))
     }
 
-    pub fn iter<'a>(&'a self) -> impl 'a + Iterator<Item = (Pos, PieceType, Color)> {
-        self.data.iter().enumerate().flat_map(move |(x, col)| {
-            col.iter()
-                .enumerate()
-                .flat_map(move |(y, &piece)| Some((Pos(y, x), piece?)))
-                .map(move |(pos, (pt, color))| (pos, pt, color))
-        })
+    /**
+     * every piece on the board with its square, going across each rank from a1 to h8
+     */
+    pub fn iter(&self) -> PieceIter<'_> {
+        PieceIter {
+            data: &self.data,
+            index: 0,
+        }
+    }
+
+    /**
+     * puts a piece on the given square, replacing whatever was there
+     */
+    pub fn place(&mut self, pos: Pos, piece: PieceType, color: Color) {
+        self.set(pos, piece, color);
+    }
+
+    /**
+     * takes the piece off of the given square, if there is one
+     */
+    pub fn clear(&mut self, pos: Pos) -> Option<Piece> {
+        self.remove(pos)
+    }
+
+    /**
+     * the number of pieces of the given type and color on the board
+     */
+    pub fn count(&self, piece: PieceType, color: Color) -> usize {
+        self.census()[color as usize][piece as usize]
+    }
+
+    /**
+     * the number of pieces of each type for each color, indexed
+     * by `[color as usize][piece as usize]`
+     */
+    pub fn census(&self) -> [[usize; 6]; 2] {
+        let mut census = [[0; 6]; 2];
+
+        for (_, piece, color) in self.iter() {
+            census[color as usize][piece as usize] += 1;
+        }
+
+        census
+    }
+
+    /**
+     * every occupied square as a bitboard, where bit `y * 8 + x` is set if
+     * there is a piece on `Pos(x, y)`
+     */
+    pub fn occupancy(&self) -> u64 {
+        self.iter().map(|(pos, _, _)| pos).collect::<Bitboard>().0
+    }
+
+    /**
+     * the squares with a piece of the given color on them, laid out like `occupancy`
+     */
+    pub fn occupancy_of(&self, color: Color) -> u64 {
+        self.iter()
+            .filter(|&(_, _, c)| c == color)
+            .map(|(pos, _, _)| pos)
+            .collect::<Bitboard>()
+            .0
+    }
+
+    /**
+     * the board turned half way around, so a1 swaps with h8 and b1 with g8,
+     * without changing the color of any piece
+     */
+    pub fn rotate_180(&self) -> RawBoard {
+        let mut data = self.data;
+        data.reverse();
+
+        for rank in data.iter_mut() {
+            rank.reverse();
+        }
+
+        RawBoard { data }
+    }
+
+    /**
+     * the board reflected left to right, so the a file swaps with the h file,
+     * without changing the color of any piece
+     */
+    pub fn mirror_files(&self) -> RawBoard {
+        let mut data = self.data;
+
+        for rank in data.iter_mut() {
+            rank.reverse();
+        }
+
+        RawBoard { data }
     }
 
     pub fn iter_mut<'a>(
         &'a mut self,
-    ) -> impl 'a + Iterator<Item = (Pos, &mut PieceType, &mut Color)> {
+    ) -> impl 'a + Iterator<Item = (Pos, &'a mut PieceType, &'a mut Color)> {
         self.data.iter_mut().enumerate().flat_map(move |(x, col)| {
             col.iter_mut()
                 .enumerate()
@@ -101,8 +554,90 @@ impl RawBoard {
     }
 }
 
+/**
+ * The pieces on a board with their squares, see `RawBoard::iter`
+ */
+#[derive(Debug, Clone)]
+pub struct PieceIter<'a> {
+    data: &'a [[Option<Piece>; 8]; 8],
+    index: usize,
+}
+
+impl Iterator for PieceIter<'_> {
+    type Item = (Pos, PieceType, Color);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.index < 64 {
+            let pos = Pos(self.index % 8, self.index / 8);
+            self.index += 1;
+
+            if let Some((piece, color)) = self.data[pos.1][pos.0] {
+                return Some((pos, piece, color));
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a> IntoIterator for &'a RawBoard {
+    type Item = (Pos, PieceType, Color);
+    type IntoIter = PieceIter<'a>;
+
+    fn into_iter(self) -> PieceIter<'a> {
+        self.iter()
+    }
+}
+
+/**
+ * the same as iterating over the board's `RawBoard`, i.e. `for (pos, piece, color) in &board`
+ */
+impl<'a> IntoIterator for &'a Board {
+    type Item = (Pos, PieceType, Color);
+    type IntoIter = PieceIter<'a>;
+
+    fn into_iter(self) -> PieceIter<'a> {
+        self.board.iter()
+    }
+}
+
+#[derive(Clone)]
 pub struct Board {
-    board: RawBoard,
+    pub(crate) board: RawBoard,
+    pub(crate) castling: CastlingRights,
+    pub(crate) turn: Color,
+    pub(crate) en_passant: Option<Pos>,
+    pub(crate) halfmove_clock: u32,
+    pub(crate) fullmove_number: u32,
+    pub(crate) castle_files: CastleFiles,
+    white_king: Option<Pos>,
+    black_king: Option<Pos>,
+    #[cfg(feature = "bitboard")]
+    pub(crate) bits: Bitboards,
+    history: Vec<(Undo, u64)>,
+}
+
+/**
+ * Everything needed to reverse an applied Diff, see `Board::apply_with_undo`
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Undo {
+    diff: Diff,
+    moved: Piece,
+    captured: Option<(Pos, Piece)>,
+    castling: CastlingRights,
+    en_passant: Option<Pos>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    turn: Color,
+}
+
+/**
+ * Everything needed to reverse a null move, see `Board::apply_null_move`
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NullUndo {
+    en_passant: Option<Pos>,
 }
 
 impl Board {
@@ -138,69 +673,603 @@ impl Board {
             board.set(Pos(i, 6), PieceType::Pawn, Color::Black);
         }
 
-        Self { board }
+        Self {
+            #[cfg(feature = "bitboard")]
+            bits: Bitboards::new(&board),
+            board,
+            castling: CastlingRights::ALL,
+            turn: Color::White,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            castle_files: CastleFiles::STANDARD,
+            white_king: Some(Pos(4, 0)),
+            black_king: Some(Pos(4, 7)),
+            history: Vec::new(),
+        }
     }
 
     pub fn with(board: RawBoard) -> Self {
-        Self { board }
+        Self::with_turn(board, Color::White)
     }
 
-    pub fn get(&self, pos: Pos) -> Result<Piece, Error> {
-        self.board.get(pos)
+    /**
+     * a board with no pieces on it, white to move, and no castling rights
+     *
+     * pieces can be added with `BoardBuilder`, or by placing them on a `RawBoard` for `with`
+     */
+    pub fn empty() -> Self {
+        Self::with(RawBoard::default())
     }
 
     /**
-     * gets all possible moves for the selected piece, check if
-     * the king will be put in check and if so, that move will be skipped
+     * a board with no pieces on it, but the same side to move, clocks, and castling files
+     * as this one, with no castling rights, en passant target, or history
      */
-    pub fn get_possible_moves<'a>(&'a self, pos: Pos) -> Option<impl 'a + Iterator<Item = Diff>> {
-        let (_, color) = self.board.get(pos).ok()?;
+    pub fn clone_empty(&self) -> Self {
+        let mut board = Self::with_turn(RawBoard::default(), self.turn);
+        board.halfmove_clock = self.halfmove_clock;
+        board.fullmove_number = self.fullmove_number;
+        board.castle_files = self.castle_files;
+        board
+    }
 
-        let diffs = self.get_possible_moves_unchecked(pos);
+    /**
+     * creates a board from a custom position, with the given color to move
+     */
+    pub fn with_turn(board: RawBoard, turn: Color) -> Self {
+        let find_king = |color| {
+            board
+                .iter()
+                .find(|&(_, pt, c)| pt == PieceType::King && c == color)
+                .map(|(pos, _, _)| pos)
+        };
 
-        diffs.map(move |diffs| {
-            diffs.filter(move |&x| {
-                let mut temp = Self { board: self.board };
-                temp.apply(x).unwrap();
-                !temp.is_king_check(color)
-            })
-        })
+        Self {
+            castling: CastlingRights::infer(&board),
+            white_king: find_king(Color::White),
+            black_king: find_king(Color::Black),
+            #[cfg(feature = "bitboard")]
+            bits: Bitboards::new(&board),
+            board,
+            turn,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            castle_files: CastleFiles::STANDARD,
+            history: Vec::new(),
+        }
     }
 
     /**
-     * gets all possible moves, don't check if the king will be put in check
+     * creates a board from a custom position like `with_turn`, but first checks
+     * that each side has exactly one king, that no pawns are on the first or
+     * last rank, and that the side not to move isn't in check
      */
-    #[allow(clippy::single_match)]
-    pub fn get_possible_moves_unchecked<'a>(
-        &'a self,
-        pos: Pos,
-    ) -> Option<impl 'a + Iterator<Item = Diff>> {
-        let (pt, color) = self.board.get(pos).ok()?;
-        let old_pos = pos;
-        let pos = pos.into();
-        let dir = color.dir();
+    pub fn with_validated(board: RawBoard, turn: Color) -> Result<Self, PositionError> {
+        for &color in &[Color::White, Color::Black] {
+            let kings = board
+                .iter()
+                .filter(|&(_, pt, c)| pt == PieceType::King && c == color)
+                .count();
 
-        let moves = pt.get_moves();
-        let moves = moves
+            match kings {
+                0 => return Err(PositionError::MissingKing(color)),
+                1 => (),
+                _ => return Err(PositionError::TooManyKings(color)),
+            }
+        }
+
+        let back_rank_pawn = board
             .iter()
-            .map(move |&VMove(_, del, ty, dist)| {
-                let del = del * dir;
-                (del, ty, dist as i32)
-            })
-            .flat_map(move |(del, ty, dist)| {
-                let mut no_captures = true;
+            .find(|&(Pos(_, y), pt, _)| pt == PieceType::Pawn && (y == 0 || y == 7));
 
-                (1..=dist)
-                    .flat_map(move |dist| Pos::try_from(pos + del * dist))
-                    .map(move |pos| {
-                        let victim = self.board.get(pos).ok();
+        if let Some((pos, _, _)) = back_rank_pawn {
+            return Err(PositionError::PawnOnBackRank(pos));
+        }
 
-                        let diff = if let Some((_, v_color)) = victim {
-                            if ty.is_capture() && v_color != color {
-                                Some(Diff {
-                                    from: old_pos,
-                                    to: pos,
-                                    ty: DiffType::Capture { cap: pos },
+        let board = Self::with_turn(board, turn);
+
+        if board.is_king_check(turn.opponent()) {
+            return Err(PositionError::OpponentInCheck);
+        }
+
+        Ok(board)
+    }
+
+    /**
+     * the square of the given color's king, or None if it has no king
+     */
+    pub fn find_king(&self, color: Color) -> Option<Pos> {
+        match color {
+            Color::White => self.white_king,
+            Color::Black => self.black_king,
+        }
+    }
+
+    /**
+     * the cached square of the given color's king, kept up to date by
+     * `apply_with_undo` and `unmake` so check detection doesn't need to search for it
+     */
+    fn king_mut(&mut self, color: Color) -> &mut Option<Pos> {
+        match color {
+            Color::White => &mut self.white_king,
+            Color::Black => &mut self.black_king,
+        }
+    }
+
+    /**
+     * the color whose turn it is to move
+     */
+    pub fn to_move(&self) -> Color {
+        self.turn
+    }
+
+    /**
+     * the square that was skipped over by a pawn's two square advance
+     * on the last move, if any
+     */
+    pub fn en_passant(&self) -> Option<Pos> {
+        self.en_passant
+    }
+
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling
+    }
+
+    /**
+     * the number of halfmoves since the last capture or pawn move
+     */
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /**
+     * the number of the current full move, starting at 1 and
+     * incremented after each of black's moves
+     */
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    /**
+     * Changes the side to move, for setting up a position
+     *
+     * This doesn't count as a move, so the clocks are left alone
+     */
+    pub fn set_turn(&mut self, color: Color) {
+        self.turn = color;
+    }
+
+    /**
+     * Sets the square a pawn skipped over on the last move, so the side to move
+     * can capture it en passant, or `None` to take away the chance
+     *
+     * This isn't checked, so there should be a pawn of the side not to move
+     * just past the square, as if it had just made its two square advance
+     */
+    pub fn set_en_passant(&mut self, pos: Option<Pos>) {
+        self.en_passant = pos;
+    }
+
+    /**
+     * Sets which castling moves are still allowed, replacing the rights inferred by `with`
+     *
+     * This isn't checked, so the kings and rooks of each right given should be
+     * on their starting squares
+     */
+    pub fn set_castling_rights(&mut self, rights: CastlingRights) {
+        self.castling = rights;
+    }
+
+    pub fn get(&self, pos: Pos) -> Result<Piece, Error> {
+        self.board.get(pos)
+    }
+
+    /**
+     * gets all possible moves for the selected piece, check if
+     * the king will be put in check and if so, that move will be skipped
+     *
+     * the board is copied once, and each candidate is checked by making
+     * the move on that copy, looking for check, and then unmaking it
+     */
+    pub fn get_possible_moves<'a>(&'a self, pos: Pos) -> Option<impl 'a + Iterator<Item = Diff>> {
+        let (_, color) = self.board.get(pos).ok()?;
+
+        let diffs = self.get_possible_moves_unchecked(pos)?;
+        let mut temp = self.scratch();
+
+        let legal = diffs.filter(move |&diff| {
+            let undo = temp.apply_with_undo(diff).unwrap();
+            let safe = !temp.is_king_check(color);
+            temp.unmake(undo);
+            safe
+        });
+
+        Some(legal.chain(self.castling_moves(pos)))
+    }
+
+    /**
+     * gets only the moves of the selected piece that capture something,
+     * including en passant and capturing promotions, useful for quiescence search
+     */
+    pub fn capture_moves<'a>(&'a self, pos: Pos) -> Option<impl 'a + Iterator<Item = Diff>> {
+        let moves = self.get_possible_moves(pos)?;
+
+        Some(moves.filter(Diff::is_capture))
+    }
+
+    /**
+     * clears `out` and fills it with the same moves as `get_possible_moves`,
+     * so a single buffer can be reused across a whole search node
+     */
+    pub fn legal_moves_into(&self, pos: Pos, out: &mut Vec<Diff>) {
+        out.clear();
+        out.extend(self.get_possible_moves(pos).into_iter().flatten());
+    }
+
+    /**
+     * Checks if the diff puts the other side's king in check, without changing the board
+     *
+     * The diff doesn't have to be legal, but if it can't be applied at all this is false
+     */
+    pub fn gives_check(&self, diff: Diff) -> bool {
+        let color = match self.board.get(diff.from) {
+            Ok((_, color)) => color,
+            Err(_) => return false,
+        };
+
+        let mut temp = self.scratch();
+        match temp.apply_with_undo(diff) {
+            Ok(_) => temp.is_king_check(color.opponent()),
+            Err(_) => false,
+        }
+    }
+
+    /**
+     * checks if the king of the given color is safe after applying the diff
+     */
+    fn is_safe_after(&self, diff: Diff, color: Color) -> bool {
+        let mut temp = self.scratch();
+        temp.apply_with_undo(diff).unwrap();
+        !temp.is_king_check(color)
+    }
+
+    /**
+     * copies the current position, without the history
+     */
+    pub(crate) fn scratch(&self) -> Self {
+        Self {
+            history: Vec::new(),
+            ..*self
+        }
+    }
+
+    /**
+     * gets the castling moves for the king on the selected square
+     *
+     * the king and rook must not have moved, and every square that either
+     * of them crosses or lands on must be empty (other than their own squares)
+     *
+     * the king may not be in check, or pass through or land on an attacked square
+     *
+     * the king always lands on the g or c file, and the rook right next to it
+     * on the f or d file, even when they start elsewhere in Chess960
+     */
+    fn castling_moves(&self, pos: Pos) -> impl Iterator<Item = Diff> {
+        let kingside = self.castle(pos, true).ok();
+        let queenside = self.castle(pos, false).ok();
+
+        kingside.into_iter().chain(queenside)
+    }
+
+    /**
+     * checks a single castling move for the king on the selected square,
+     * and gives the reason if it isn't legal
+     */
+    fn castle(&self, pos: Pos, kingside: bool) -> Result<Diff, MoveLegality> {
+        let files = self.castle_files;
+
+        let color = match self.board.get(pos) {
+            Ok((PieceType::King, color)) => color,
+            _ => return Err(MoveLegality::WrongPattern),
+        };
+
+        let (rights, rook_x, king_to, rook_to) = if kingside {
+            (CastlingRights::kingside(color), files.kingside, 6, 5)
+        } else {
+            (CastlingRights::queenside(color), files.queenside, 2, 3)
+        };
+
+        let Pos(x, y) = pos;
+        let span = |a: usize, b: usize| a.min(b)..=a.max(b);
+
+        if pos != Pos(files.king, back_row(color)) || !self.castling.contains(rights) {
+            return Err(MoveLegality::WrongPattern);
+        }
+
+        if self.board.get(Pos(rook_x, y)).ok() != Some((PieceType::Rook, color)) {
+            return Err(MoveLegality::WrongPattern);
+        }
+
+        let blocked = span(x, king_to)
+            .chain(span(rook_x, rook_to))
+            .filter(|&file| file != x && file != rook_x)
+            .any(|file| self.board.get(Pos(file, y)).is_ok());
+
+        if blocked {
+            return Err(MoveLegality::BlockedPath);
+        }
+
+        // this includes the square the king is on, so it can't castle out of check
+        if span(x, king_to).any(|file| self.is_square_attacked(Pos(file, y), color.opponent())) {
+            return Err(MoveLegality::LeavesKingInCheck);
+        }
+
+        let diff = Diff {
+            ty: DiffType::Castle {
+                rook_from: Pos(rook_x, y),
+                rook_to: Pos(rook_to, y),
+            },
+            from: pos,
+            to: Pos(king_to, y),
+        };
+
+        if self.is_safe_after(diff, color) {
+            Ok(diff)
+        } else {
+            Err(MoveLegality::LeavesKingInCheck)
+        }
+    }
+
+    /**
+     * Checks if the diff is legal like `is_legal`, and if it isn't, why not
+     *
+     * A move that the piece could make if nothing were in the way is `BlockedPath`,
+     * this includes moving onto a piece of the same color. A diff with the right
+     * squares but the wrong `DiffType` is `WrongPattern`
+     */
+    pub fn classify_move(&self, diff: Diff) -> MoveLegality {
+        let Diff { ty, from, to } = diff;
+
+        let (piece, color) = match self.board.get(from) {
+            Ok(piece) => piece,
+            Err(_) => return MoveLegality::NoPiece,
+        };
+
+        if color != self.turn {
+            return MoveLegality::NotYourTurn;
+        }
+
+        if let DiffType::Castle { .. } = ty {
+            let castle = match to.0 {
+                6 => self.castle(from, true),
+                2 => self.castle(from, false),
+                _ => Err(MoveLegality::WrongPattern),
+            };
+
+            return match castle {
+                Ok(legal) if legal == diff => MoveLegality::Legal,
+                Ok(_) => MoveLegality::WrongPattern,
+                Err(reason) => reason,
+            };
+        }
+
+        let mut same_squares = false;
+        for candidate in self
+            .get_possible_moves_unchecked(from)
+            .into_iter()
+            .flatten()
+        {
+            if candidate == diff {
+                return if self.is_safe_after(diff, color) {
+                    MoveLegality::Legal
+                } else {
+                    MoveLegality::LeavesKingInCheck
+                };
+            }
+
+            same_squares |= candidate.to == to;
+        }
+
+        if same_squares {
+            return MoveLegality::WrongPattern;
+        }
+
+        // the piece alone on the board, with and without whatever is on the target square
+        let reaches = |victim: Option<Piece>| {
+            let mut alone = RawBoard::default();
+            alone.set(from, piece, color);
+
+            if let Some((piece, color)) = victim {
+                alone.set(to, piece, color);
+            }
+
+            Board::with_turn(alone, color)
+                .get_possible_moves_unchecked(from)
+                .into_iter()
+                .flatten()
+                .any(|candidate| candidate.to == to)
+        };
+
+        let victim = self.board.get(to).ok().filter(|&(_, c)| c != color);
+
+        if reaches(None) || (victim.is_some() && reaches(victim)) {
+            MoveLegality::BlockedPath
+        } else {
+            MoveLegality::WrongPattern
+        }
+    }
+
+    /**
+     * gets all legal moves for every piece of the given color
+     */
+    pub fn all_legal_moves<'a>(&'a self, color: Color) -> impl 'a + Iterator<Item = Diff> {
+        self.pieces(color)
+            .flat_map(move |(pos, _)| self.get_possible_moves(pos).unwrap())
+    }
+
+    /**
+     * Gets the same moves as `all_legal_moves`, but when the given color is in check
+     * only the moves that could get it out of check are generated in the first place
+     *
+     * These are king moves, captures of the checking piece, and moves onto the squares
+     * between a checking slider and the king. In double check only the king can move
+     */
+    pub fn evasions<'a>(&'a self, color: Color) -> impl 'a + Iterator<Item = Diff> {
+        // the squares that any piece other than the king has to move to or capture on
+        let targets = match self.find_king(color) {
+            Some(king) => match self.attackers_of(king, color.opponent())[..] {
+                [] => !0,
+                [checker] => {
+                    let mut targets = bit(checker);
+
+                    if let Ok((PieceType::Queen | PieceType::Rook | PieceType::Bishop, _)) =
+                        self.board.get(checker)
+                    {
+                        for pos in Pos::between(checker, king).into_iter().flatten() {
+                            targets |= bit(pos);
+                        }
+                    }
+
+                    targets
+                }
+                _ => 0,
+            },
+            None => !0,
+        };
+
+        self.pieces(color).flat_map(move |(pos, piece)| {
+            let is_king = piece == PieceType::King;
+
+            let resolves = move |diff: &Diff| {
+                let squares = match diff.ty {
+                    DiffType::EnPassant { captured } => bit(diff.to) | bit(captured),
+                    _ => bit(diff.to),
+                };
+
+                is_king || targets & squares != 0
+            };
+
+            let moves = if is_king || targets != 0 {
+                self.get_possible_moves_unchecked(pos)
+            } else {
+                None
+            };
+
+            moves
+                .into_iter()
+                .flatten()
+                .filter(resolves)
+                .filter(move |&diff| self.is_safe_after(diff, color))
+                .chain(self.castling_moves(pos))
+        })
+    }
+
+    /**
+     * Pairs every legal move for the side to move with the board after it is played
+     *
+     * This clones the whole board, history included, for every move, so it is meant
+     * for simple tree walks. Searches should prefer `apply_with_undo` and `unmake`
+     */
+    pub fn successors<'a>(&'a self) -> impl 'a + Iterator<Item = (Diff, Board)> {
+        self.all_legal_moves(self.turn).map(move |diff| {
+            let mut board = self.clone();
+            board.apply(diff).unwrap();
+            (diff, board)
+        })
+    }
+
+    /**
+     * counts the legal moves of every piece of the given color
+     */
+    pub fn legal_move_count(&self, color: Color) -> usize {
+        self.pieces(color)
+            .map(|(pos, _)| self.get_possible_moves(pos).unwrap().count())
+            .sum()
+    }
+
+    /**
+     * every piece on the board, read from the bitboards when they're enabled
+     */
+    #[cfg(feature = "bitboard")]
+    fn all_pieces(&self) -> impl '_ + Iterator<Item = (Pos, PieceType, Color)> {
+        self.bits.iter()
+    }
+
+    #[cfg(not(feature = "bitboard"))]
+    fn all_pieces(&self) -> impl '_ + Iterator<Item = (Pos, PieceType, Color)> {
+        self.board.iter()
+    }
+
+    /**
+     * every piece of the given color, along with the square it's on
+     */
+    pub fn pieces<'a>(&'a self, color: Color) -> impl 'a + Iterator<Item = (Pos, PieceType)> {
+        self.all_pieces()
+            .filter(move |&(_, _, c)| c == color)
+            .map(|(pos, piece, _)| (pos, piece))
+    }
+
+    /**
+     * gets all possible moves, don't check if the king will be put in check
+     */
+    pub fn get_possible_moves_unchecked<'a>(
+        &'a self,
+        pos: Pos,
+    ) -> Option<impl 'a + Iterator<Item = Diff>> {
+        let (pt, color) = self.board.get(pos).ok()?;
+        let old_pos = pos;
+        let pos = pos.into();
+        let dir = color.dir();
+        let home_row = (1 - dir) / 2 * 5 + 1; // choose 1 and 6
+
+        // knights and kings only ever take a single step, so their targets are precomputed
+        let (moves, leaps): (&[VMove], &[Pos]) = match pt {
+            PieceType::Knight => (&[], tables::KNIGHT.get(old_pos)),
+            PieceType::King => (&[], tables::KING.get(old_pos)),
+            _ => (pt.get_moves(), &[]),
+        };
+
+        let leaps = leaps.iter().flat_map(move |&to| match self.board.get(to) {
+            Ok((_, v_color)) if v_color == color => None,
+            Ok(_) => Some(Diff {
+                from: old_pos,
+                to,
+                ty: DiffType::Capture { cap: to },
+            }),
+            Err(_) => Some(Diff {
+                ty: DiffType::Move,
+                from: old_pos,
+                to,
+            }),
+        });
+
+        let moves = moves
+            .iter()
+            .map(move |&VMove(pt, del, ty, dist)| {
+                let del = del * dir;
+                let dist = match pt {
+                    // pawns may only advance two squares from their starting row
+                    PieceType::Pawn if ty.is_move_only() && pos.y != home_row => 1,
+                    _ => dist,
+                };
+                (del, ty, dist as i32)
+            })
+            .flat_map(move |(del, ty, dist)| {
+                let mut no_captures = true;
+
+                (1..=dist)
+                    .flat_map(move |dist| Pos::try_from(pos + del * dist))
+                    .map(move |pos| {
+                        let victim = self.board.get(pos).ok();
+
+                        let diff = if let Some((_, v_color)) = victim {
+                            if ty.is_capture() && v_color != color {
+                                Some(Diff {
+                                    from: old_pos,
+                                    to: pos,
+                                    ty: DiffType::Capture { cap: pos },
                                 })
                             } else {
                                 None
@@ -224,77 +1293,587 @@ impl Board {
                     })
                     .flat_map(move |(diff, _)| diff)
                     .fuse()
-            });
+            })
+            .chain(leaps)
+            .flat_map(move |diff| {
+                // pawns that reach the last row must be promoted
+                let promotes = pt == PieceType::Pawn && diff.to.1 == 7 - back_row(color);
+                let capture = diff.ty != DiffType::Move;
 
-        match pt {
-            PieceType::Pawn => {}
-            PieceType::King => {}
-            _ => (), // intentionally unimplemented, other pieces don't need special casing
-        }
+                let count = if promotes { PROMOTIONS.len() } else { 1 };
+
+                PROMOTIONS[..count].iter().map(move |&piece| {
+                    if promotes {
+                        Diff {
+                            ty: DiffType::Promote { piece, capture },
+                            ..diff
+                        }
+                    } else {
+                        diff
+                    }
+                })
+            })
+            .chain(self.en_passant_move(old_pos));
 
         Some(moves)
     }
 
     /**
-     * Checks and applies a Diff to the current state of the Board
+     * gets the en passant capture for the pawn on the selected square, if any
      */
-    pub fn apply(&mut self, Diff { ty, from, to }: Diff) -> Result<(), Error> {
-        match ty {
-            DiffType::Move => {
-                let (piece, color) = self.board.remove(from).ok_or(Error::NoPiece)?;
-
-                if self.board.get(to).is_ok() {
-                    Err(InvalidDiff::CaptureOnMoveTy)?;
-                }
-
-                self.board.set(to, piece, color);
-            }
-            DiffType::Capture { cap } => {
-                let (piece, color) = self.board.remove(from).ok_or(Error::NoPiece)?;
+    pub(crate) fn en_passant_move(&self, from: Pos) -> Option<Diff> {
+        let (pt, color) = self.board.get(from).ok()?;
+        let to = self.en_passant?;
 
-                if self.board.replace(cap, None).is_none() {
-                    Err(InvalidDiff::MoveOnCaptureTy)?;
-                }
+        let del = to.into() - from.into();
+        if pt != PieceType::Pawn || del.x.abs() != 1 || del.y != color.dir() {
+            return None;
+        }
 
-                self.board.set(to, piece, color);
-            }
-            DiffType::Promote { piece } => {
-                match self.board.replace(from, None) {
-                    Some((PieceType::Pawn, color)) => {
-                        let row = (1 - color.dir()) / 2 * 5 + 1; // choose 1 and 6
-                        let prom = (1 - color.dir()) / 2 * 8; // choose 0 and 8
-
-                        let from = from.into();
-                        let v_to = to.into();
-                        if from.y == row && v_to.y == prom {
-                            self.board.set(to, piece, color)
-                        } else {
-                            Err(InvalidDiff::InvalidPromotionRow)?
-                        }
-                    }
-                    Some(_) => Err(InvalidDiff::InvalidPromotionPiece)?,
-                    None => Err(Error::NoPiece)?,
-                }
-            }
+        let captured = Pos(to.0, from.1);
+        match self.board.get(captured) {
+            Ok((PieceType::Pawn, c)) if c != color => Some(Diff {
+                ty: DiffType::EnPassant { captured },
+                from,
+                to,
+            }),
+            _ => None,
         }
+    }
 
+    /**
+     * Checks and applies a Diff to the current state of the Board,
+     * if the Diff is malformed the Board is left unchanged
+     */
+    pub fn apply(&mut self, diff: Diff) -> Result<(), Error> {
+        let hash = self.zobrist_hash();
+        let undo = self.apply_with_undo(diff)?;
+        self.history.push((undo, hash));
         Ok(())
     }
 
     /**
-     * This checks if the king of the given color is in check,
-     * i.e. is being attacked by an enemy piece
+     * Checks if the Diff is exactly one of the legal moves for the side to move,
+     * including its type, so a castle or en passant capture must be written as one
      */
-    fn is_king_check(&self, color: Color) -> bool {
+    pub fn is_legal(&self, diff: Diff) -> bool {
+        match self.board.get(diff.from) {
+            Ok((_, color)) if color == self.turn => (),
+            _ => return false,
+        }
+
+        self.get_possible_moves(diff.from)
+            .is_some_and(|mut moves| moves.any(|legal| legal == diff))
+    }
+
+    /**
+     * Applies a Diff only if it is a legal move for the side to move,
+     * unlike `apply` which only checks that the Diff is well formed
+     */
+    pub fn make_move(&mut self, diff: Diff) -> Result<(), Error> {
+        if !self.is_legal(diff) {
+            return Err(Error::IllegalMove);
+        }
+
+        self.apply(diff)
+    }
+
+    /**
+     * Reverses the last applied Diff, restoring the previous state of the Board
+     */
+    pub fn undo(&mut self) -> Result<(), Error> {
+        let (undo, _) = self.history.pop().ok_or(Error::NoHistory)?;
+        self.unmake(undo);
+        Ok(())
+    }
+
+    /**
+     * The total value of the pieces of the given color
+     */
+    pub fn material(&self, color: Color) -> u32 {
         self.board
             .iter()
-            .filter(move |(_, _, c)| c != &color)
-            .flat_map(move |(pos, _, _)| {
-                self.get_possible_moves_unchecked(pos)
+            .filter(|&(_, _, c)| c == color)
+            .map(|(_, piece, _)| piece.value())
+            .sum()
+    }
+
+    /**
+     * White's material minus Black's, in pawns, so it is positive when White is ahead
+     */
+    pub fn material_balance(&self) -> i32 {
+        self.board
+            .iter()
+            .map(|(_, piece, color)| match color {
+                Color::White => piece.value() as i32,
+                Color::Black => -(piece.value() as i32),
+            })
+            .sum()
+    }
+
+    /**
+     * Checks if the current position has occurred at least three times
+     * over the moves recorded in the history
+     */
+    pub fn is_threefold_repetition(&self) -> bool {
+        let hash = self.zobrist_hash();
+
+        let count = self.history.iter().filter(|&&(_, h)| h == hash).count();
+
+        count + 1 >= 3
+    }
+
+    /**
+     * Checks if a hundred halfmoves (fifty moves by each side) have been
+     * made without a capture or pawn move
+     */
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= 100
+    }
+
+    /**
+     * Checks if neither side has enough material left to checkmate
+     *
+     * This is the case for king vs king, king and a minor piece vs king,
+     * and when every remaining minor piece is a bishop on the same color square
+     */
+    pub fn is_insufficient_material(&self) -> bool {
+        let mut minors = Vec::new();
+
+        for (pos, piece, _) in self.board.iter() {
+            match piece {
+                PieceType::King => (),
+                PieceType::Knight | PieceType::Bishop => minors.push((pos, piece)),
+                PieceType::Pawn | PieceType::Rook | PieceType::Queen => return false,
+            }
+        }
+
+        match minors.as_slice() {
+            [] | [_] => true,
+            [(first, _), ..] => minors.iter().all(|&(pos, piece)| {
+                piece == PieceType::Bishop && pos.is_light() == first.is_light()
+            }),
+        }
+    }
+
+    /**
+     * Checks and applies a Diff, and returns the token needed to reverse it with `unmake`
+     *
+     * Unlike `apply`, this doesn't record the Diff in the history, so it can't be
+     * reversed with `undo`, but it also never allocates
+     */
+    pub fn apply_with_undo(&mut self, diff: Diff) -> Result<Undo, Error> {
+        let Diff { ty, from, to } = diff;
+        let moved = self.board.get(from)?;
+
+        let captured = match ty {
+            DiffType::Capture { cap } => Some(cap),
+            DiffType::EnPassant { captured } => Some(captured),
+            DiffType::Promote { capture: true, .. } => Some(to),
+            _ => None,
+        };
+
+        let undo = Undo {
+            diff,
+            moved,
+            captured: captured.and_then(|pos| Some((pos, self.board.get(pos).ok()?))),
+            castling: self.castling,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            turn: self.turn,
+        };
+
+        // everything is checked before anything changes, so a failed apply leaves the board as
+        // it was. `vacated` are the squares that the move empties before it places its pieces
+        let occupied =
+            |pos: Pos, vacated: &[Pos]| !vacated.contains(&pos) && self.board.get(pos).is_ok();
+        let (piece, color) = moved;
+
+        match ty {
+            DiffType::Move => {
+                if occupied(to, &[from]) {
+                    Err(InvalidDiff::CaptureOnMoveTy)?;
+                }
+            }
+            DiffType::Capture { cap } => {
+                if !occupied(cap, &[from]) {
+                    Err(InvalidDiff::MoveOnCaptureTy)?;
+                }
+            }
+            DiffType::Promote {
+                piece: target,
+                capture,
+            } => {
+                if let PieceType::King | PieceType::Pawn = target {
+                    Err(InvalidDiff::InvalidPromotionTarget)?
+                }
+
+                if piece != PieceType::Pawn {
+                    Err(InvalidDiff::InvalidPromotionPiece)?
+                }
+
+                let row = (1 + color.dir()) / 2 * 5 + 1; // choose 6 and 1
+                let prom = (1 + color.dir()) / 2 * 7; // choose 7 and 0
+
+                if from.into().y != row || to.into().y != prom {
+                    Err(InvalidDiff::InvalidPromotionRow)?
+                }
+
+                match (capture, occupied(to, &[from])) {
+                    (true, false) => Err(InvalidDiff::MoveOnCaptureTy)?,
+                    (false, true) => Err(InvalidDiff::CaptureOnMoveTy)?,
+                    _ => (),
+                }
+            }
+            DiffType::Castle { rook_from, rook_to } => {
+                let rook = match self.board.get(rook_from) {
+                    Ok(rook) if rook_from != from => rook,
+                    _ => Err(Error::NoPiece(rook_from))?,
+                };
+
+                match (moved, rook) {
+                    ((PieceType::King, k), (PieceType::Rook, r)) if k == r => (),
+                    _ => Err(InvalidDiff::InvalidCastlePieces)?,
+                }
+
+                if occupied(to, &[from, rook_from]) || occupied(rook_to, &[from, rook_from]) {
+                    Err(InvalidDiff::CaptureOnMoveTy)?;
+                }
+            }
+            DiffType::EnPassant { captured } => {
+                if piece != PieceType::Pawn {
+                    Err(InvalidDiff::InvalidEnPassantPiece)?;
+                }
+
+                match self.board.get(captured) {
+                    Ok((PieceType::Pawn, c)) if captured != from && c != color => (),
+                    Ok(_) if captured != from => Err(InvalidDiff::InvalidEnPassantPiece)?,
+                    _ => Err(InvalidDiff::MoveOnCaptureTy)?,
+                }
+
+                if occupied(to, &[from, captured]) {
+                    Err(InvalidDiff::CaptureOnMoveTy)?;
+                }
+            }
+        }
+
+        match ty {
+            DiffType::Castle { rook_from, rook_to } => {
+                self.board.remove(from);
+                self.board.remove(rook_from);
+                self.board.set(to, PieceType::King, color);
+                self.board.set(rook_to, PieceType::Rook, color);
+            }
+            _ => {
+                self.board.remove(from);
+
+                if let Some((pos, _)) = undo.captured {
+                    self.board.remove(pos);
+                }
+
+                let piece = match ty {
+                    DiffType::Promote { piece, .. } => piece,
+                    _ => piece,
+                };
+
+                self.board.set(to, piece, color);
+            }
+        }
+
+        // a pawn that advances two squares may be captured en passant on the next move
+        self.en_passant = match (moved, ty) {
+            ((PieceType::Pawn, _), DiffType::Move) if (to.into() - from.into()).y.abs() == 2 => {
+                Some(Pos(from.0, (from.1 + to.1) / 2))
+            }
+            _ => None,
+        };
+
+        self.halfmove_clock = match (moved, ty) {
+            ((PieceType::Pawn, _), _) | (_, DiffType::Capture { .. }) => 0,
+            _ => self.halfmove_clock + 1,
+        };
+
+        if self.turn == Color::Black {
+            self.fullmove_number += 1;
+        }
+
+        if let (PieceType::King, color) = moved {
+            *self.king_mut(color) = Some(to);
+        }
+
+        if let Some((_, (PieceType::King, color))) = undo.captured {
+            *self.king_mut(color) = None;
+        }
+
+        self.castling
+            .remove(CastlingRights::revoked_by(self.castle_files, from));
+        self.castling
+            .remove(CastlingRights::revoked_by(self.castle_files, to));
+        self.turn = self.turn.opponent();
+
+        #[cfg(feature = "bitboard")]
+        self.sync_bitboards(diff);
+
+        Ok(undo)
+    }
+
+    /**
+     * updates the bitboards on every square the diff touched
+     */
+    #[cfg(feature = "bitboard")]
+    fn sync_bitboards(&mut self, Diff { ty, from, to }: Diff) {
+        let extra = match ty {
+            DiffType::Castle { rook_from, rook_to } => [Some(rook_from), Some(rook_to)],
+            DiffType::Capture { cap } => [Some(cap), None],
+            DiffType::EnPassant { captured } => [Some(captured), None],
+            DiffType::Promote { .. } | DiffType::Move => [None, None],
+        };
+
+        for &pos in [from, to].iter().chain(extra.iter().flatten()) {
+            self.bits.sync(&self.board, pos);
+        }
+    }
+
+    /**
+     * Passes the turn to the other side without moving, for null-move pruning in a search
+     *
+     * This only changes the side to move and clears the en passant target, so the
+     * clocks and history are left alone. It is reversed with `undo_null_move`
+     *
+     * # Panics
+     *
+     * If the side to move is in check, since passing would leave its king capturable
+     */
+    pub fn apply_null_move(&mut self) -> NullUndo {
+        assert!(
+            !self.is_king_check(self.turn),
+            "a null move can't be made while in check"
+        );
+
+        self.turn = self.turn.opponent();
+
+        NullUndo {
+            en_passant: self.en_passant.take(),
+        }
+    }
+
+    /**
+     * Reverses a null move, the token must be from the last move applied to this board
+     */
+    pub fn undo_null_move(&mut self, undo: NullUndo) {
+        self.turn = self.turn.opponent();
+        self.en_passant = undo.en_passant;
+    }
+
+    /**
+     * Reverses a Diff applied by `apply_with_undo`, the token must be from
+     * the last Diff applied to this board
+     */
+    pub fn unmake(&mut self, undo: Undo) {
+        let Diff { ty, from, to } = undo.diff;
+
+        match ty {
+            DiffType::Castle { rook_from, rook_to } => {
+                let king = self.board.remove(to);
+                let rook = self.board.remove(rook_to);
+                self.board.replace(from, king);
+                self.board.replace(rook_from, rook);
+            }
+            _ => {
+                self.board.remove(to);
+                self.board.replace(from, Some(undo.moved));
+            }
+        }
+
+        if let Some((pos, piece)) = undo.captured {
+            self.board.replace(pos, Some(piece));
+
+            if let (PieceType::King, color) = piece {
+                *self.king_mut(color) = Some(pos);
+            }
+        }
+
+        if let (PieceType::King, color) = undo.moved {
+            *self.king_mut(color) = Some(from);
+        }
+
+        self.castling = undo.castling;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.fullmove_number = undo.fullmove_number;
+        self.turn = undo.turn;
+
+        #[cfg(feature = "bitboard")]
+        self.sync_bitboards(undo.diff);
+    }
+
+    /**
+     * Counts the number of positions reachable by legal moves after exactly
+     * `depth` moves, used to validate move generation
+     */
+    pub fn perft(&mut self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.all_legal_moves(self.turn).collect::<Vec<_>>();
+
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        moves
+            .into_iter()
+            .map(|diff| {
+                let undo = self.apply_with_undo(diff).unwrap();
+                let count = self.perft(depth - 1);
+                self.unmake(undo);
+                count
+            })
+            .sum()
+    }
+
+    /**
+     * Gets every piece of the given color that is absolutely pinned to its king,
+     * along with the direction from the piece towards the king
+     *
+     * This walks each rook and bishop ray out from the king, and a piece is pinned
+     * if it is the first piece on the ray and the next one is an enemy slider
+     * that moves along that ray
+     */
+    pub fn pinned_pieces(&self, color: Color) -> Vec<(Pos, Vector)> {
+        let king = match self.find_king(color) {
+            Some(king) => king,
+            None => return Vec::new(),
+        };
+
+        let rays = PieceType::Rook
+            .get_moves()
+            .iter()
+            .chain(PieceType::Bishop.get_moves())
+            .map(|&VMove(pt, del, _, _)| (pt, del));
+
+        rays.filter_map(|(slider, del)| {
+            let mut pieces = (1..8)
+                .map(|dist| Pos::try_from(king.into() + del * dist))
+                .take_while(Result::is_ok)
+                .flat_map(|pos| {
+                    let pos = pos.ok()?;
+                    Some((pos, self.board.get(pos).ok()?))
+                });
+
+            let (pinned, (_, pinned_color)) = pieces.next()?;
+            let (_, (pinner, pinner_color)) = pieces.next()?;
+
+            let is_pin = pinned_color == color
+                && pinner_color != color
+                && (pinner == slider || pinner == PieceType::Queen);
+
+            if is_pin {
+                Some((pinned, (king.into() - pinned.into()).step()))
+            } else {
+                None
+            }
+        })
+        .collect()
+    }
+
+    /**
+     * Gets the squares of every piece of the given color that attacks the target square,
+     * whether or not the target is empty or occupied by either color
+     *
+     * This doesn't check if moving the attacker would put its own king in check
+     */
+    pub fn attackers_of(&self, target: Pos, by: Color) -> Vec<Pos> {
+        // put an enemy piece on the target, so that every attack on it shows up as a capture
+        let mut temp = self.scratch();
+        temp.board.set(target, PieceType::Pawn, by.opponent());
+
+        temp.board
+            .iter()
+            .filter(|&(_, _, color)| color == by)
+            .filter(|&(pos, _, _)| {
+                temp.get_possible_moves_unchecked(pos)
                     .unwrap()
-                    .flat_map(move |Diff { to, .. }| self.get(to))
+                    .any(|diff| diff.to == target)
+            })
+            .map(|(pos, _, _)| pos)
+            .collect()
+    }
+
+    /**
+     * The squares of the enemy pieces giving check to the given color's king,
+     * none if it isn't in check, and two in a double check where only the king can move
+     *
+     * This is empty if the color has no king
+     */
+    pub fn checkers(&self, color: Color) -> Vec<Pos> {
+        self.find_king(color)
+            .map(|king| self.attackers_of(king, color.opponent()))
+            .unwrap_or_default()
+    }
+
+    /**
+     * This checks if the king of the given color is in check,
+     * i.e. is being attacked by an enemy piece
+     */
+    fn is_king_check(&self, color: Color) -> bool {
+        self.find_king(color)
+            .is_some_and(|pos| self.is_square_attacked(pos, color.opponent()))
+    }
+
+    /**
+     * Checks if any piece of the given color attacks the square,
+     * whether or not the square is empty
+     *
+     * With the `bitboard` feature this is answered from the bitboards,
+     * otherwise see `mailbox_attacked`
+     */
+    pub fn is_square_attacked(&self, pos: Pos, by: Color) -> bool {
+        #[cfg(feature = "bitboard")]
+        {
+            self.bits.is_attacked(pos, by)
+        }
+
+        #[cfg(not(feature = "bitboard"))]
+        {
+            self.mailbox_attacked(pos, by)
+        }
+    }
+
+    /**
+     * This works backwards from the square, following each kind of piece's
+     * moves in reverse to see if that kind of piece is at the other end
+     */
+    #[cfg_attr(feature = "bitboard", allow(dead_code))]
+    pub(crate) fn mailbox_attacked(&self, pos: Pos, by: Color) -> bool {
+        let target = pos.into();
+        let dir = by.dir();
+
+        let pieces = [
+            PieceType::King,
+            PieceType::Queen,
+            PieceType::Rook,
+            PieceType::Bishop,
+            PieceType::Knight,
+            PieceType::Pawn,
+        ];
+
+        pieces
+            .iter()
+            .flat_map(|pt| pt.get_moves())
+            .filter(|&&VMove(_, _, ty, _)| ty.is_capture())
+            .any(|&VMove(pt, del, _, dist)| {
+                let del = del * dir;
+
+                (1..=dist as i32)
+                    .map(|dist| Pos::try_from(target - del * dist))
+                    .take_while(Result::is_ok)
+                    .flat_map(|pos| self.board.get(pos.ok()?).ok())
+                    .next()
+                    == Some((pt, by))
             })
-            .any(move |(pt, c)| pt == PieceType::King && c == color)
     }
 
     /**
@@ -306,152 +1885,2178 @@ impl Board {
      * Stale => King is not being attacked, and no piece of the given color can move
      */
     pub fn game_condition(&self, color: Color) -> GameCondition {
-        let has_moves = self
-            .board
-            .iter()
-            .filter(move |(_, _, c)| c == &color)
-            .flat_map(move |(pos, _, _)| self.get_possible_moves(pos).unwrap())
-            .any(move |_| true);
+        let has_moves = self.legal_move_count(color) != 0;
+
+        let is_king_check = self.is_king_check(color);
+
+        match (is_king_check, has_moves) {
+            (true, true) => GameCondition::Check,
+            (true, false) => GameCondition::Mate,
+            (false, true) => GameCondition::Safe,
+            (false, false) => GameCondition::Stale,
+        }
+    }
+
+    /**
+     * checks if the side to move has been checkmated
+     */
+    pub fn is_checkmate(&self) -> bool {
+        self.game_condition(self.to_move()) == GameCondition::Mate
+    }
+
+    /**
+     * checks if the side to move has no legal moves, but isn't in check
+     */
+    pub fn is_stalemate(&self) -> bool {
+        self.game_condition(self.to_move()) == GameCondition::Stale
+    }
+
+    /**
+     * The overall result of the game from the side to move's point of view
+     *
+     * Mate => the side to move has lost
+     * Draw => stalemate, the fifty-move rule, insufficient material, or threefold repetition
+     */
+    pub fn result(&self) -> GameResult {
+        match self.game_condition(self.turn) {
+            GameCondition::Mate => match self.turn {
+                Color::White => GameResult::BlackWins,
+                Color::Black => GameResult::WhiteWins,
+            },
+            GameCondition::Stale => GameResult::Draw,
+            GameCondition::Check | GameCondition::Safe => {
+                if self.is_fifty_move_draw()
+                    || self.is_insufficient_material()
+                    || self.is_threefold_repetition()
+                {
+                    GameResult::Draw
+                } else {
+                    GameResult::Ongoing
+                }
+            }
+        }
+    }
+
+    /**
+     * Checks if the position is drawn by stalemate, the fifty-move rule, insufficient
+     * material, or threefold repetition, the same draws as `result`
+     *
+     * Checkmate takes precedence, so a mate on the move that reaches the fifty-move
+     * limit isn't a draw. Draws by agreement aren't part of the position, see `Game`
+     */
+    pub fn is_draw(&self) -> bool {
+        self.result() == GameResult::Draw
+    }
+
+    /**
+     * The same position color-flipped (a vertical mirror), so black's pieces become white's
+     * and are mirrored onto the other side of the board, rank 1 becoming rank 8
+     *
+     * This isn't a 180 degree turn like `RawBoard::rotate_180`, which would also swap the
+     * a and h files and put the kings on the d file where they can't castle. The files stay
+     * the same so that the kings and rooks can still castle, and the side to move, castling
+     * rights, and en passant target are all swapped to match. The new board has no history
+     */
+    pub fn flipped(&self) -> Board {
+        let mirror = |Pos(x, y): Pos| Pos(x, 7 - y);
+
+        let mut board = RawBoard::default();
+        for (pos, piece, color) in self.board.iter() {
+            board.set(mirror(pos), piece, color.opponent());
+        }
+
+        let mut castling = CastlingRights::none();
+        for &color in &[Color::White, Color::Black] {
+            if self.castling.contains(CastlingRights::kingside(color)) {
+                castling.insert(CastlingRights::kingside(color.opponent()));
+            }
+
+            if self.castling.contains(CastlingRights::queenside(color)) {
+                castling.insert(CastlingRights::queenside(color.opponent()));
+            }
+        }
+
+        let mut flipped = Board::with_turn(board, self.turn.opponent());
+        flipped.castling = castling;
+        flipped.castle_files = self.castle_files;
+        flipped.en_passant = self.en_passant.map(mirror);
+        flipped.halfmove_clock = self.halfmove_clock;
+        flipped.fullmove_number = self.fullmove_number;
+        flipped
+    }
+
+    /**
+     * The same position reflected left to right, so the a file becomes the h file
+     *
+     * The colors and side to move stay the same. A rook that could castle kingside
+     * ends up on the queenside, so the castling rights are swapped to match, and
+     * castling then follows the Chess960 rules since the king is no longer on the
+     * e file. The new board has no history to undo
+     */
+    pub fn mirror(&self) -> Board {
+        let mirror = |Pos(x, y): Pos| Pos(7 - x, y);
+        let board = self.board.mirror_files();
+
+        let mut castling = CastlingRights::none();
+        for &color in &[Color::White, Color::Black] {
+            if self.castling.contains(CastlingRights::kingside(color)) {
+                castling.insert(CastlingRights::queenside(color));
+            }
+
+            if self.castling.contains(CastlingRights::queenside(color)) {
+                castling.insert(CastlingRights::kingside(color));
+            }
+        }
+
+        let mut mirrored = Board::with_turn(board, self.turn);
+        mirrored.castling = castling;
+        mirrored.castle_files = CastleFiles {
+            king: 7 - self.castle_files.king,
+            kingside: 7 - self.castle_files.queenside,
+            queenside: 7 - self.castle_files.kingside,
+        };
+        mirrored.en_passant = self.en_passant.map(mirror);
+        mirrored.halfmove_clock = self.halfmove_clock;
+        mirrored.fullmove_number = self.fullmove_number;
+        mirrored
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+ * boards are equal if they hold the same position, everything that FEN records and
+ * the castling files, no matter which moves were played to reach it
+ */
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.board == other.board
+            && self.turn == other.turn
+            && self.castling == other.castling
+            && self.en_passant == other.en_passant
+            && self.halfmove_clock == other.halfmove_clock
+            && self.fullmove_number == other.fullmove_number
+            && self.castle_files == other.castle_files
+    }
+}
+
+impl Eq for Board {}
+
+impl core::ops::Index<Pos> for Board {
+    type Output = Option<Piece>;
+
+    fn index(&self, Pos(x, y): Pos) -> &Self::Output {
+        &self.board.data[y][x]
+    }
+}
+
+mod fmt {
+    use super::*;
+    use core::fmt;
+
+    impl fmt::Display for Pos {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{}{}", self.file_char(), self.1 + 1)
+        }
+    }
+
+    /**
+     * the move in coordinate form, i.e. `e2-e4`, `d5xe6`, or `e7xf8=Q`,
+     * with castling written as `O-O` or `O-O-O`
+     */
+    impl fmt::Display for Diff {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let Diff { ty, from, to } = *self;
+
+            match ty {
+                DiffType::Castle { rook_from, .. } if rook_from.0 > from.0 => write!(f, "O-O"),
+                DiffType::Castle { .. } => write!(f, "O-O-O"),
+                DiffType::Promote { piece, .. } => {
+                    let sep = if self.is_capture() { 'x' } else { '-' };
+                    write!(f, "{}{}{}={}", from, sep, to, piece.get_ident())
+                }
+                _ => {
+                    let sep = if self.is_capture() { 'x' } else { '-' };
+                    write!(f, "{}{}{}", from, sep, to)
+                }
+            }
+        }
+    }
+
+    impl fmt::Debug for Board {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            for col in self.board.data.iter().rev() {
+                for &tile in col {
+                    match tile {
+                        Some((pt, _)) => write!(f, "{}. ", pt.get_ident())?,
+                        None => write!(f, "__ ")?,
+                    }
+                }
+
+                writeln!(f)?;
+
+                for &tile in col {
+                    match tile {
+                        Some((_, color)) => write!(f, ".{} ", color.get_ident())?,
+                        None => write!(f, "__ ")?,
+                    }
+                }
+
+                writeln!(f)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    /**
+     * writes the board with rank and file labels, using `glyph` to draw each piece
+     */
+    fn diagram<W: fmt::Write>(
+        board: &Board,
+        f: &mut W,
+        glyph: impl Fn(PieceType, Color) -> char,
+    ) -> fmt::Result {
+        for (y, col) in board.board.data.iter().enumerate().rev() {
+            write!(f, "{}", y + 1)?;
+
+            for &tile in col {
+                match tile {
+                    Some((pt, color)) => write!(f, " {}", glyph(pt, color))?,
+                    None => write!(f, " .")?,
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        write!(f, " ")?;
+        for file in "abcdefgh".chars() {
+            write!(f, " {}", file)?;
+        }
+
+        writeln!(f)
+    }
+
+    impl fmt::Display for Board {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            diagram(self, f, |pt, color| match color {
+                Color::White => pt.get_ident(),
+                Color::Black => pt.get_ident().to_ascii_lowercase(),
+            })
+        }
+    }
+
+    impl Board {
+        /**
+         * like the `Display` impl, but draws the pieces with their figurine glyphs
+         */
+        pub fn to_unicode(&self) -> String {
+            let mut out = String::new();
+            diagram(self, &mut out, PieceType::unicode).unwrap();
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::board::*;
+
+    macro_rules! pos {
+        ($x:expr, $y:expr) => {
+            Pos::new_unchecked($x, $y)
+        };
+    }
+
+    macro_rules! poss_move_u {
+        ($board:expr, $x:expr, $y:expr) => {
+            $board
+                .get_possible_moves_unchecked(pos!($x, $y))
+                .unwrap()
+                .collect::<Vec<_>>()
+        };
+    }
+
+    macro_rules! make_board {
+        (
+            $(($($rest:tt)*))*
+        ) => {{
+            #[allow(unused_mut)]
+            let mut board = RawBoard { data: [[None; 8]; 8] };
+
+            $(
+                make_board!(@internal board $($rest)*);
+            )*
+
+            dbg!(Board::with(board))
+        }};
+
+        (@internal $board:ident ($x:expr, $y:expr) $color:ident $piece:ident) => {
+            $board.set(Pos($x, $y), PieceType::$piece, Color::$color);
+        };
+    }
+
+    #[test]
+    fn gpmu_pass_1() {
+        let board = Board::new();
+
+        let moves = poss_move_u!(board, 0, 1);
+
+        assert_eq!(moves.len(), 2);
+        assert!(moves.contains(&Diff {
+            ty: DiffType::Move,
+            from: pos!(0, 1),
+            to: pos!(0, 2)
+        }));
+        assert!(moves.contains(&Diff {
+            ty: DiffType::Move,
+            from: pos!(0, 1),
+            to: pos!(0, 3)
+        }));
+    }
+
+    #[test]
+    fn gpmu_pass_2() {
+        let board = Board::new();
+
+        let moves = poss_move_u!(board, 1, 0);
+
+        assert_eq!(moves.len(), 2);
+        assert!(moves.contains(&Diff {
+            ty: DiffType::Move,
+            from: pos!(1, 0),
+            to: pos!(0, 2)
+        }));
+        assert!(moves.contains(&Diff {
+            ty: DiffType::Move,
+            from: pos!(1, 0),
+            to: pos!(2, 2)
+        }));
+    }
+
+    #[test]
+    fn gpmu_pass_3() {
+        let board = Board::new();
+
+        let moves = poss_move_u!(board, 0, 0);
+        assert!(moves.is_empty());
+
+        let moves = poss_move_u!(board, 2, 0);
+        assert!(moves.is_empty());
+
+        let moves = poss_move_u!(board, 3, 0);
+        assert!(moves.is_empty());
+
+        let moves = poss_move_u!(board, 4, 0);
+        assert!(moves.is_empty());
+    }
+
+    #[test]
+    fn gpmu_pawn_double_step_home_row() {
+        let board = make_board!(
+            ((4, 4) White Pawn)
+            ((2, 6) Black Pawn)
+        );
+
+        let moves = poss_move_u!(board, 4, 4);
+        assert_eq!(
+            moves,
+            [Diff {
+                ty: DiffType::Move,
+                from: pos!(4, 4),
+                to: pos!(4, 5)
+            }]
+        );
+
+        let moves = poss_move_u!(board, 2, 6);
+        assert_eq!(moves.len(), 2);
+        assert!(moves.contains(&Diff {
+            ty: DiffType::Move,
+            from: pos!(2, 6),
+            to: pos!(2, 4)
+        }));
+    }
+
+    #[test]
+    fn gpmu_pawn_double_step_blocked() {
+        let board = make_board!(
+            ((0, 1) White Pawn)
+            ((0, 2) Black Rook)
+            ((3, 1) White Pawn)
+            ((3, 3) Black Rook)
+        );
+
+        let moves = poss_move_u!(board, 0, 1);
+        assert!(moves.is_empty());
+
+        let moves = poss_move_u!(board, 3, 1);
+        assert_eq!(
+            moves,
+            [Diff {
+                ty: DiffType::Move,
+                from: pos!(3, 1),
+                to: pos!(3, 2)
+            }]
+        );
+    }
+
+    macro_rules! castles {
+        ($board:expr, $x:expr, $y:expr) => {
+            $board
+                .get_possible_moves(pos!($x, $y))
+                .unwrap()
+                .filter(|diff| match diff.ty {
+                    DiffType::Castle { .. } => true,
+                    _ => false,
+                })
+                .collect::<Vec<_>>()
+        };
+    }
+
+    #[test]
+    fn castle_both_sides() {
+        let board = make_board!(
+            ((4, 0) White King)
+            ((0, 0) White Rook)
+            ((7, 0) White Rook)
+            ((4, 7) Black King)
+            ((0, 7) Black Rook)
+            ((7, 7) Black Rook)
+        );
+
+        assert_eq!(board.castling_rights(), CastlingRights::ALL);
+
+        for &y in &[0, 7] {
+            let moves = castles!(board, 4, y);
+
+            assert_eq!(moves.len(), 2);
+            assert!(moves.contains(&Diff {
+                ty: DiffType::Castle {
+                    rook_from: pos!(7, y),
+                    rook_to: pos!(5, y)
+                },
+                from: pos!(4, y),
+                to: pos!(6, y)
+            }));
+            assert!(moves.contains(&Diff {
+                ty: DiffType::Castle {
+                    rook_from: pos!(0, y),
+                    rook_to: pos!(3, y)
+                },
+                from: pos!(4, y),
+                to: pos!(2, y)
+            }));
+        }
+    }
+
+    #[test]
+    fn castle_apply() {
+        let mut board = make_board!(
+            ((4, 0) White King)
+            ((0, 0) White Rook)
+            ((7, 0) White Rook)
+            ((4, 7) Black King)
+            ((0, 7) Black Rook)
+        );
+
+        board
+            .apply(Diff {
+                ty: DiffType::Castle {
+                    rook_from: pos!(7, 0),
+                    rook_to: pos!(5, 0),
+                },
+                from: pos!(4, 0),
+                to: pos!(6, 0),
+            })
+            .unwrap();
+
+        assert_eq!(
+            board.get(pos!(6, 0)).ok(),
+            Some((PieceType::King, Color::White))
+        );
+        assert_eq!(
+            board.get(pos!(5, 0)).ok(),
+            Some((PieceType::Rook, Color::White))
+        );
+        assert!(board.get(pos!(4, 0)).is_err());
+        assert!(board.get(pos!(7, 0)).is_err());
+        assert_eq!(board.castling_rights(), CastlingRights::BLACK_QUEENSIDE);
+
+        board
+            .apply(Diff {
+                ty: DiffType::Castle {
+                    rook_from: pos!(0, 7),
+                    rook_to: pos!(3, 7),
+                },
+                from: pos!(4, 7),
+                to: pos!(2, 7),
+            })
+            .unwrap();
+
+        assert_eq!(
+            board.get(pos!(2, 7)).ok(),
+            Some((PieceType::King, Color::Black))
+        );
+        assert_eq!(
+            board.get(pos!(3, 7)).ok(),
+            Some((PieceType::Rook, Color::Black))
+        );
+        assert_eq!(board.castling_rights(), CastlingRights::none());
+    }
+
+    #[test]
+    fn castle_blocked() {
+        let board = make_board!(
+            ((4, 0) White King)
+            ((0, 0) White Rook)
+            ((1, 0) White Knight)
+            ((7, 0) White Rook)
+            ((6, 0) Black Bishop)
+            ((4, 7) Black King)
+            ((0, 7) Black Rook)
+            ((7, 7) Black Rook)
+        );
+
+        assert!(castles!(board, 4, 0).is_empty());
+        assert_eq!(castles!(board, 4, 7).len(), 2);
+    }
+
+    #[test]
+    fn castle_through_attack() {
+        let board = make_board!(
+            ((4, 0) White King)
+            ((0, 0) White Rook)
+            ((7, 0) White Rook)
+            ((5, 5) Black Rook)
+            ((2, 5) Black Rook)
+            ((4, 7) Black King)
+            ((0, 7) Black Rook)
+            ((7, 7) Black Rook)
+            ((7, 5) White Knight)
+            ((6, 3) White Bishop)
+        );
+
+        // f1 and c1 are attacked
+        assert!(castles!(board, 4, 0).is_empty());
+
+        // the knight attacks g8, and the bishop attacks c8
+        assert!(castles!(board, 4, 7).is_empty());
+    }
+
+    #[test]
+    fn castle_rights_revoked() {
+        let mut board = make_board!(
+            ((4, 0) White King)
+            ((0, 0) White Rook)
+            ((7, 0) White Rook)
+            ((4, 7) Black King)
+            ((0, 7) Black Rook)
+            ((7, 7) Black Rook)
+        );
+
+        let moves = [
+            (pos!(7, 0), pos!(7, 1)),
+            (pos!(4, 7), pos!(3, 7)),
+            (pos!(7, 1), pos!(7, 0)),
+            (pos!(3, 7), pos!(4, 7)),
+        ];
+
+        for &(from, to) in &moves {
+            board
+                .apply(Diff {
+                    ty: DiffType::Move,
+                    from,
+                    to,
+                })
+                .unwrap();
+        }
+
+        // moving the pieces back doesn't restore the rights
+        assert_eq!(board.castling_rights(), CastlingRights::WHITE_QUEENSIDE);
+        assert_eq!(castles!(board, 4, 0).len(), 1);
+
+        let mut board = make_board!(
+            ((4, 0) White King)
+            ((0, 0) White Rook)
+            ((7, 0) White Rook)
+            ((4, 7) Black King)
+            ((3, 7) Black Knight)
+            ((0, 7) Black Rook)
+            ((7, 7) Black Rook)
+        );
+
+        // the knight on d8 keeps the rook on a8 from giving check
+        board
+            .apply(Diff {
+                ty: DiffType::Capture { cap: pos!(0, 7) },
+                from: pos!(0, 0),
+                to: pos!(0, 7),
+            })
+            .unwrap();
+
+        // the captured rook takes black's queenside rights with it
+        let mut rights = CastlingRights::none();
+        rights.insert(CastlingRights::WHITE_KINGSIDE);
+        rights.insert(CastlingRights::BLACK_KINGSIDE);
+        assert_eq!(board.castling_rights(), rights);
+        assert_eq!(castles!(board, 4, 7).len(), 1);
+
+        board.undo().unwrap();
+        assert_eq!(board.castling_rights(), CastlingRights::ALL);
+    }
+
+    #[test]
+    fn castle_in_check() {
+        let board = make_board!(
+            ((4, 0) White King)
+            ((0, 0) White Rook)
+            ((7, 0) White Rook)
+            ((4, 5) Black Rook)
+            ((0, 7) Black King)
+        );
+
+        // the squares between are empty and safe, but the king is in check
+        assert!(!board.is_square_attacked(pos!(5, 0), Color::Black));
+        assert!(!board.is_square_attacked(pos!(3, 0), Color::Black));
+        assert!(castles!(board, 4, 0).is_empty());
+    }
+
+    #[test]
+    fn make_move() {
+        let mut board = Board::new();
+
+        let black = Diff {
+            ty: DiffType::Move,
+            from: pos!(4, 6),
+            to: pos!(4, 4),
+        };
+        match board.make_move(black) {
+            Err(Error::IllegalMove) => (),
+            err => panic!("{:?}", err),
+        }
+
+        let too_far = Diff {
+            ty: DiffType::Move,
+            from: pos!(4, 1),
+            to: pos!(4, 4),
+        };
+        match board.make_move(too_far) {
+            Err(Error::IllegalMove) => (),
+            err => panic!("{:?}", err),
+        }
+
+        assert!(board == Board::new());
+
+        board
+            .make_move(Diff {
+                ty: DiffType::Move,
+                from: pos!(4, 1),
+                to: pos!(4, 3),
+            })
+            .unwrap();
+        board.make_move(black).unwrap();
+
+        assert_eq!(board.to_move(), Color::White);
+        assert_eq!(
+            board.get(pos!(4, 4)).ok(),
+            Some((PieceType::Pawn, Color::Black))
+        );
+    }
+
+    #[test]
+    fn is_legal() {
+        let board = Board::new();
+
+        let push = Diff {
+            ty: DiffType::Move,
+            from: pos!(4, 1),
+            to: pos!(4, 3),
+        };
+        assert!(board.is_legal(push));
+
+        let diagonal = Diff {
+            ty: DiffType::Move,
+            from: pos!(4, 1),
+            to: pos!(5, 2),
+        };
+        assert!(!board.is_legal(diagonal));
+
+        let black = Diff {
+            ty: DiffType::Move,
+            from: pos!(4, 6),
+            to: pos!(4, 4),
+        };
+        assert!(!board.is_legal(black));
+
+        // the right squares, but written as a plain move instead of a castle
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        let castle = board.parse_san("O-O").unwrap();
+        assert!(board.is_legal(castle));
+        assert!(!board.is_legal(Diff {
+            ty: DiffType::Move,
+            ..castle
+        }));
+    }
+
+    #[test]
+    fn flipped_vertical_mirror() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w Kq - 0 1")
+                .unwrap();
+        let flipped = board.flipped();
+
+        assert_eq!(
+            flipped.to_fen(),
+            "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b Qk - 0 1"
+        );
+        assert_eq!(flipped.evaluate(), -board.evaluate());
+        assert_eq!(flipped.clone().perft(2), board.clone().perft(2));
+        assert!(flipped.flipped() == board);
+
+        let board =
+            Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+        let flipped = board.flipped();
+
+        assert_eq!(flipped.en_passant(), Some(pos!(5, 2)));
+        assert_eq!(flipped.evaluate(), -board.evaluate());
+        assert!(flipped.flipped() == board);
+    }
+
+    #[test]
+    fn mirror() {
+        // the king and queen swap files, so no legal position mirrors to itself
+        let board = Board::new();
+        let mirrored = board.mirror();
+
+        assert_eq!(
+            mirrored.to_fen(),
+            "rnbkqbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBKQBNR w KQkq - 0 1"
+        );
+        assert_eq!(mirrored.evaluate(), 0);
+        assert!(mirrored.mirror() == board);
+
+        let board = Board::from_fen("r3k3/8/8/3pP3/8/8/8/4K2R w Kq d6 0 1").unwrap();
+        let mut mirrored = board.mirror();
+
+        assert_eq!(mirrored.to_fen(), "3k3r/8/8/3Pp3/8/8/8/R2K4 w Qk e6 0 1");
+        // only one ply deep, since the castled kings don't land on mirrored squares
+        assert_eq!(mirrored.clone().perft(1), board.clone().perft(1));
+        assert!(mirrored.mirror() == board);
+
+        // the old kingside castle is now a queenside castle with the rook on a1
+        let castle = mirrored.parse_san("O-O-O").unwrap();
+        mirrored.make_move(castle).unwrap();
+        assert_eq!(mirrored.to_ascii().lines().last(), Some("..KR...."));
+    }
+
+    #[test]
+    fn gives_check() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+
+        let check = board.parse_san("Ra8+").unwrap();
+        assert!(board.gives_check(check));
+
+        let quiet = board.parse_san("Ra7").unwrap();
+        assert!(!board.gives_check(quiet));
+
+        // moving the bishop out of the way uncovers the rook
+        let board = Board::from_fen("4k3/8/8/8/8/8/4B3/4RK2 w - - 0 1").unwrap();
+        let discovered = board.parse_san("Bb5").unwrap();
+        assert!(board.gives_check(discovered));
+
+        assert!(!board.gives_check(Diff {
+            ty: DiffType::Move,
+            from: pos!(0, 3),
+            to: pos!(0, 4),
+        }));
+    }
+
+    #[test]
+    fn successors() {
+        let board = Board::new();
+        let successors = board.successors().collect::<Vec<_>>();
+
+        assert_eq!(successors.len(), 20);
+
+        for (diff, after) in successors {
+            assert_eq!(after.to_move(), Color::Black);
+            assert!(after.get(diff.to).is_ok());
+
+            let mut after = after;
+            after.undo().unwrap();
+            assert!(after == board);
+        }
+    }
+
+    #[test]
+    fn classify_move() {
+        let diff = |ty, from, to| Diff { ty, from, to };
+
+        // the knight on d2 is pinned to the king by the bishop on a5
+        let board = Board::from_fen("4k3/8/8/b7/8/8/3N4/4K2R w K - 0 1").unwrap();
+        assert_eq!(
+            board.classify_move(diff(DiffType::Move, Pos::D2, Pos::F3)),
+            MoveLegality::LeavesKingInCheck
+        );
+        assert_eq!(
+            board.classify_move(diff(DiffType::Move, Pos::E1, Pos::E2)),
+            MoveLegality::Legal
+        );
+        assert_eq!(
+            board.classify_move(diff(DiffType::Move, Pos::E8, Pos::E7)),
+            MoveLegality::NotYourTurn
+        );
+        assert_eq!(
+            board.classify_move(diff(DiffType::Move, Pos::C3, Pos::C4)),
+            MoveLegality::NoPiece
+        );
+
+        // the king is in the rook's way, and the rook can't move diagonally
+        assert_eq!(
+            board.classify_move(diff(DiffType::Move, Pos::H1, Pos::A1)),
+            MoveLegality::BlockedPath
+        );
+        assert_eq!(
+            board.classify_move(diff(DiffType::Move, Pos::H1, Pos::G2)),
+            MoveLegality::WrongPattern
+        );
+        assert_eq!(
+            board.classify_move(diff(DiffType::Move, Pos::D2, Pos::D4)),
+            MoveLegality::WrongPattern
+        );
+
+        // the pinned knight still shields the king, so it can castle
+        let castle = diff(
+            DiffType::Castle {
+                rook_from: Pos::H1,
+                rook_to: Pos::F1,
+            },
+            Pos::E1,
+            Pos::G1,
+        );
+        assert_eq!(board.classify_move(castle), MoveLegality::Legal);
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K1NR w K - 0 1").unwrap();
+        assert_eq!(board.classify_move(castle), MoveLegality::BlockedPath);
+        assert_eq!(
+            board.classify_move(diff(DiffType::Move, Pos::E2, Pos::E5)),
+            MoveLegality::WrongPattern
+        );
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        assert_eq!(board.classify_move(castle), MoveLegality::Legal);
+        assert_eq!(
+            board.classify_move(diff(DiffType::Move, Pos::E1, Pos::G1)),
+            MoveLegality::WrongPattern
+        );
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/r3K2R w K - 0 1").unwrap();
+        assert_eq!(board.classify_move(castle), MoveLegality::LeavesKingInCheck);
+
+        // the pawn can push, but not onto the knight, or diagonally onto an empty square
+        let board = Board::from_fen("4k3/8/8/8/8/4n3/4P3/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.classify_move(diff(DiffType::Move, Pos::E2, Pos::E3)),
+            MoveLegality::BlockedPath
+        );
+        assert_eq!(
+            board.classify_move(diff(DiffType::Move, Pos::E2, Pos::D3)),
+            MoveLegality::WrongPattern
+        );
+        assert_eq!(
+            board.classify_move(diff(DiffType::Move, Pos::E1, Pos::E3)),
+            MoveLegality::WrongPattern
+        );
+    }
+
+    #[test]
+    fn turn_flips_on_apply() {
+        let mut board = Board::new();
+        assert_eq!(board.to_move(), Color::White);
+
+        board
+            .apply(Diff {
+                ty: DiffType::Move,
+                from: pos!(4, 1),
+                to: pos!(4, 3),
+            })
+            .unwrap();
+        assert_eq!(board.to_move(), Color::Black);
+
+        board
+            .apply(Diff {
+                ty: DiffType::Move,
+                from: pos!(4, 6),
+                to: pos!(4, 4),
+            })
+            .unwrap();
+        assert_eq!(board.to_move(), Color::White);
+
+        let board = Board::with_turn(RawBoard::default(), Color::Black);
+        assert_eq!(board.to_move(), Color::Black);
+    }
+
+    #[test]
+    fn gpmu_promotion() {
+        let board = make_board!(
+            ((0, 6) White Pawn)
+            ((1, 7) Black Rook)
+            ((5, 1) Black Pawn)
+        );
+
+        let moves = poss_move_u!(board, 0, 6);
+        assert_eq!(moves.len(), 8);
+
+        for &piece in &PROMOTIONS {
+            assert!(moves.contains(&Diff {
+                ty: DiffType::Promote {
+                    piece,
+                    capture: false
+                },
+                from: pos!(0, 6),
+                to: pos!(0, 7)
+            }));
+            assert!(moves.contains(&Diff {
+                ty: DiffType::Promote {
+                    piece,
+                    capture: true
+                },
+                from: pos!(0, 6),
+                to: pos!(1, 7)
+            }));
+        }
+
+        let moves = poss_move_u!(board, 5, 1);
+        assert_eq!(moves.len(), 4);
+        assert!(moves.iter().all(|diff| diff.to == pos!(5, 0)));
+    }
+
+    #[test]
+    fn apply_promotion() {
+        let mut board = make_board!(
+            ((0, 6) White Pawn)
+            ((1, 7) Black Rook)
+        );
+
+        board
+            .apply(Diff {
+                ty: DiffType::Promote {
+                    piece: PieceType::Knight,
+                    capture: true,
+                },
+                from: pos!(0, 6),
+                to: pos!(1, 7),
+            })
+            .unwrap();
+
+        assert_eq!(
+            board.get(pos!(1, 7)).ok(),
+            Some((PieceType::Knight, Color::White))
+        );
+        assert!(board.get(pos!(0, 6)).is_err());
+    }
+
+    #[test]
+    fn apply_promotion_target() {
+        for &piece in &[PieceType::King, PieceType::Pawn] {
+            let mut board = make_board!(
+                ((0, 6) White Pawn)
+                ((7, 0) White King)
+                ((7, 7) Black King)
+            );
+
+            let err = board.apply(Diff {
+                ty: DiffType::Promote {
+                    piece,
+                    capture: false,
+                },
+                from: pos!(0, 6),
+                to: pos!(0, 7),
+            });
+
+            match err {
+                Err(Error::InvalidDiff(InvalidDiff::InvalidPromotionTarget)) => (),
+                err => panic!("{:?}", err),
+            }
+
+            assert_eq!(
+                board.get(pos!(0, 6)).ok(),
+                Some((PieceType::Pawn, Color::White))
+            );
+            assert!(board.get(pos!(0, 7)).is_err());
+        }
+    }
+
+    #[test]
+    fn en_passant() {
+        let mut board = make_board!(
+            ((4, 1) White Pawn)
+            ((3, 3) Black Pawn)
+            ((0, 6) Black Pawn)
+        );
+
+        assert!(board.en_passant().is_none());
+
+        board
+            .apply(Diff {
+                ty: DiffType::Move,
+                from: pos!(4, 1),
+                to: pos!(4, 3),
+            })
+            .unwrap();
+
+        assert_eq!(board.en_passant(), Some(pos!(4, 2)));
+
+        let diff = Diff {
+            ty: DiffType::EnPassant {
+                captured: pos!(4, 3),
+            },
+            from: pos!(3, 3),
+            to: pos!(4, 2),
+        };
+
+        let moves = poss_move_u!(board, 3, 3);
+        assert_eq!(moves.len(), 2);
+        assert!(moves.contains(&diff));
+
+        let mut other = board.clone();
+        other
+            .apply(Diff {
+                ty: DiffType::Move,
+                from: pos!(0, 6),
+                to: pos!(0, 5),
+            })
+            .unwrap();
+        assert!(other.en_passant().is_none());
+
+        board.apply(diff).unwrap();
+
+        assert_eq!(
+            board.get(pos!(4, 2)).ok(),
+            Some((PieceType::Pawn, Color::Black))
+        );
+        assert!(board.get(pos!(4, 3)).is_err());
+        assert!(board.get(pos!(3, 3)).is_err());
+        assert!(board.en_passant().is_none());
+    }
+
+    #[test]
+    fn pos_algebraic() {
+        assert_eq!("e4".parse::<Pos>(), Ok(pos!(4, 3)));
+        assert_eq!("a1".parse::<Pos>(), Ok(pos!(0, 0)));
+        assert_eq!("h8".parse::<Pos>(), Ok(pos!(7, 7)));
+
+        assert_eq!(pos!(4, 3).to_string(), "e4");
+        assert_eq!(pos!(0, 0).to_string(), "a1");
+        assert_eq!(pos!(7, 7).to_string(), "h8");
+
+        for s in &["e9", "i1", "", "e", "e44", "E4", "4e"] {
+            assert_eq!(s.parse::<Pos>(), Err(ParsePosError));
+        }
+    }
+
+    #[test]
+    fn pos_const() {
+        const E4: Pos = Pos::new_const(4, 3);
+        const H8: Pos = Pos::new_unchecked(7, 7);
+
+        assert_eq!(E4, "e4".parse().unwrap());
+        assert_eq!(H8.to_string(), "h8");
+        assert!(std::panic::catch_unwind(|| Pos::new_const(8, 0)).is_err());
+    }
+
+    #[test]
+    fn pos_named() {
+        assert_eq!(Pos::A1, Pos::new_unchecked(0, 0));
+        assert_eq!(Pos::E4, pos!(4, 3));
+        assert_eq!(Pos::H8, pos!(7, 7));
+
+        let named = [
+            Pos::A1,
+            Pos::B1,
+            Pos::H1,
+            Pos::A2,
+            Pos::D5,
+            Pos::G7,
+            Pos::C8,
+        ];
+        for pos in &named {
+            assert_eq!(Ok(*pos), pos.to_string().parse());
+        }
+        assert_eq!(Pos::D5.to_string(), "d5");
+        assert_eq!(Pos::G7.to_string(), "g7");
+    }
+
+    #[test]
+    fn pos_ord() {
+        let all = Pos::all().collect::<Vec<_>>();
+
+        // 37 is coprime with 64, so this visits every square in a scrambled order
+        let mut shuffled = (0..64).map(|i| all[i * 37 % 64]).collect::<Vec<_>>();
+        assert_ne!(shuffled, all);
+
+        shuffled.sort();
+        assert_eq!(shuffled, all);
+
+        assert!(Pos::H1 < Pos::A2);
+        assert!(Pos::E4 < Pos::E5);
+        assert!(Pos::A8 > Pos::H7);
+    }
+
+    #[test]
+    fn pos_all() {
+        let all = Pos::all().collect::<Vec<_>>();
+        let distinct = all.iter().collect::<std::collections::HashSet<_>>();
+
+        assert_eq!(all.len(), 64);
+        assert_eq!(distinct.len(), 64);
+        assert_eq!(all[0], pos!(0, 0));
+        assert_eq!(all[1], pos!(1, 0));
+        assert_eq!(all[63], pos!(7, 7));
+    }
+
+    #[test]
+    fn pos_file_rank() {
+        let e4 = "e4".parse::<Pos>().unwrap();
+
+        assert_eq!(e4.file_char(), 'e');
+        assert_eq!(e4.file(), 4);
+        assert_eq!(e4.rank(), 3);
+
+        assert_eq!(Pos::A1.file_char(), 'a');
+        assert_eq!(Pos::H8.file_char(), 'h');
+        assert_eq!((Pos::H8.file(), Pos::H8.rank()), (7, 7));
+    }
+
+    #[test]
+    fn pos_offset() {
+        assert_eq!(Pos::E2.offset(0, 2), Some(Pos::E4));
+        assert_eq!(Pos::E4.offset(-1, -1), Some(Pos::D3));
+        assert_eq!(Pos::G1.offset(-1, 2), Some(Pos::F3));
+        assert_eq!(Pos::E4.offset(0, 0), Some(Pos::E4));
+
+        assert_eq!(Pos::H1.offset(1, 0), None);
+        assert_eq!(Pos::A1.offset(0, -1), None);
+        assert_eq!(Pos::E7.offset(0, 2), None);
+    }
+
+    #[test]
+    fn pos_between() {
+        let between = |a: Pos, b: Pos| Pos::between(a, b).map(Iterator::collect::<Vec<_>>);
+
+        assert_eq!(
+            between(Pos::A1, Pos::E1),
+            Some(vec![Pos::B1, Pos::C1, Pos::D1])
+        );
+        assert_eq!(
+            between(Pos::E1, Pos::A1),
+            Some(vec![Pos::D1, Pos::C1, Pos::B1])
+        );
+        assert_eq!(between(Pos::C1, Pos::F4), Some(vec![Pos::D2, Pos::E3]));
+        assert_eq!(
+            between(Pos::H1, Pos::A8).map(|squares| squares.len()),
+            Some(6)
+        );
+        assert_eq!(between(Pos::E2, Pos::E3), Some(vec![]));
+
+        assert_eq!(between(Pos::B1, Pos::C3), None);
+        assert_eq!(between(Pos::A1, Pos::H7), None);
+        assert_eq!(between(Pos::E4, Pos::E4), None);
+    }
+
+    #[test]
+    fn display() {
+        let board = Board::new();
+
+        assert_eq!(
+            board.to_string(),
+            "\
+8 r n b q k b n r
+7 p p p p p p p p
+6 . . . . . . . .
+5 . . . . . . . .
+4 . . . . . . . .
+3 . . . . . . . .
+2 P P P P P P P P
+1 R N B Q K B N R
+  a b c d e f g h
+"
+        );
+    }
+
+    #[test]
+    fn display_diff() {
+        let board = Board::from_fen("r4b1k/4P3/8/3pP3/8/8/8/R3K2R w KQ d6 0 1").unwrap();
+        let san = |san| board.parse_san(san).unwrap().to_string();
+
+        assert_eq!(san("Ra4"), "a1-a4");
+        assert_eq!(san("Rxa8"), "a1xa8");
+        assert_eq!(san("exd6"), "e5xd6");
+        assert_eq!(san("exf8=N"), "e7xf8=N");
+        assert_eq!(san("e8=Q"), "e7-e8=Q");
+        assert_eq!(san("O-O"), "O-O");
+        assert_eq!(san("O-O-O"), "O-O-O");
+    }
+
+    #[test]
+    fn to_unicode() {
+        let board = Board::new();
+
+        assert_eq!(
+            board.to_unicode(),
+            "\
+8 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜
+7 ♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟
+6 . . . . . . . .
+5 . . . . . . . .
+4 . . . . . . . .
+3 . . . . . . . .
+2 ♙ ♙ ♙ ♙ ♙ ♙ ♙ ♙
+1 ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖
+  a b c d e f g h
+"
+        );
+    }
+
+    #[test]
+    fn undo() {
+        let mut board = Board::new();
+
+        assert!(board.undo().is_err());
+
+        let moves = [
+            "e4", "d5", "exd5", "Nf6", "Bb5+", "c6", "dxc6", "Qb6", "cxb7+", "Kd8", "bxa8=Q",
+            "Qc7", "Nf3", "e5", "O-O", "Bc5",
+        ];
+
+        for san in moves.iter() {
+            let diff = board.parse_san(san).unwrap();
+            board.apply(diff).unwrap();
+        }
+
+        let after_moves = board.clone();
+
+        board.apply(board.parse_san("d4").unwrap()).unwrap();
+        board.apply(board.parse_san("exd4").unwrap()).unwrap();
+        board.undo().unwrap();
+        board.undo().unwrap();
+
+        assert!(board == after_moves);
+
+        for _ in moves.iter() {
+            board.undo().unwrap();
+        }
+
+        assert!(board == Board::new());
+        assert!(board.undo().is_err());
+    }
+
+    #[test]
+    fn setters() {
+        let mut board = make_board!(
+            ((4, 0) White King)
+            ((7, 0) White Rook)
+            ((4, 4) White Pawn)
+            ((3, 4) Black Pawn)
+            ((4, 7) Black King)
+        );
+
+        let en_passant = Diff {
+            ty: DiffType::EnPassant { captured: Pos::D5 },
+            from: Pos::E5,
+            to: Pos::D6,
+        };
+        let castle = board.parse_san("O-O").unwrap();
+
+        assert!(!board.is_legal(en_passant));
+        board.set_en_passant(Some(Pos::D6));
+        assert!(board
+            .get_possible_moves(Pos::E5)
+            .unwrap()
+            .any(|diff| diff == en_passant));
+        assert_eq!(board.to_fen(), "4k3/8/8/3pP3/8/8/8/4K2R w K d6 0 1");
+
+        board.set_castling_rights(CastlingRights::none());
+        assert!(!board.is_legal(castle));
+
+        board.set_castling_rights(CastlingRights::WHITE_KINGSIDE);
+        assert!(board.is_legal(castle));
+
+        board.set_turn(Color::Black);
+        board.set_en_passant(None);
+        assert_eq!(board.to_move(), Color::Black);
+        assert_eq!(board.to_fen(), "4k3/8/8/3pP3/8/8/8/4K2R b K - 0 1");
+    }
+
+    #[test]
+    fn null_move() {
+        let fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+        let mut board = Board::from_fen(fen).unwrap();
+
+        let undo = board.apply_null_move();
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3"
+        );
+
+        board.undo_null_move(undo);
+        assert_eq!(board.to_fen(), fen);
+        assert!(board.is_legal(board.parse_uci("e5f6").unwrap()));
+    }
+
+    #[test]
+    #[should_panic(expected = "while in check")]
+    fn null_move_in_check() {
+        let mut board = Board::from_fen("4r1k1/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        board.apply_null_move();
+    }
+
+    #[test]
+    fn apply_with_undo() {
+        let fens = [
+            crate::START_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/Pp2P3/2N2Q1p/1PPBBPPP/R3K2R b KQkq a3 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+        ];
+
+        for &fen in &fens {
+            let mut board = Board::from_fen(fen).unwrap();
+
+            let moves = board.all_legal_moves(board.to_move()).collect::<Vec<_>>();
+
+            for diff in moves {
+                let undo = board.apply_with_undo(diff).unwrap();
+                assert_ne!(board.to_fen(), fen);
+
+                board.unmake(undo);
+                assert_eq!(board.to_fen(), fen);
+            }
+
+            assert!(board == Board::from_fen(fen).unwrap());
+        }
+    }
+
+    #[test]
+    fn eq_ignores_history() {
+        let mut board = Board::new();
+
+        for san in &["Nf3", "Nf6", "Ng1", "Ng8"] {
+            board.apply(board.parse_san(san).unwrap()).unwrap();
+        }
+
+        assert!(board == Board::from_fen(&board.to_fen()).unwrap());
+        assert!(board != Board::new());
+
+        board.set_turn(Color::Black);
+        assert!(
+            board
+                != Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 4 3")
+                    .unwrap()
+        );
+    }
+
+    #[test]
+    fn failed_apply_changes_nothing() {
+        let mut board = Board::new();
+
+        for san in &["f3", "e6"] {
+            board.apply(board.parse_san(san).unwrap()).unwrap();
+        }
+
+        let fen = board.to_fen();
+        let pieces = board.pieces(Color::White).collect::<Vec<_>>();
+
+        let diffs = [
+            // a stale move onto the pawn that has since moved there
+            Diff {
+                ty: DiffType::Move,
+                from: Pos::G1,
+                to: Pos::F3,
+            },
+            Diff {
+                ty: DiffType::Capture { cap: Pos::E5 },
+                from: Pos::D1,
+                to: Pos::E5,
+            },
+            Diff {
+                ty: DiffType::Castle {
+                    rook_from: Pos::H1,
+                    rook_to: Pos::F1,
+                },
+                from: Pos::E1,
+                to: Pos::G1,
+            },
+            // en passant of a friendly pawn
+            Diff {
+                ty: DiffType::EnPassant { captured: Pos::E2 },
+                from: Pos::F3,
+                to: Pos::E4,
+            },
+            Diff {
+                ty: DiffType::Promote {
+                    piece: PieceType::Queen,
+                    capture: false,
+                },
+                from: Pos::A2,
+                to: Pos::A3,
+            },
+        ];
+
+        for &diff in &diffs {
+            assert!(board.apply(diff).is_err());
+            assert_eq!(board.to_fen(), fen);
+            assert_eq!(board.pieces(Color::White).collect::<Vec<_>>(), pieces);
+        }
+
+        board.undo().unwrap();
+        board.undo().unwrap();
+        assert!(board == Board::new());
+    }
+
+    #[test]
+    fn undo_out_of_turn() {
+        let mut board = Board::new();
+        let diff = Diff {
+            ty: DiffType::Move,
+            from: Pos::E7,
+            to: Pos::E5,
+        };
+
+        // apply doesn't check whose turn it is, but undoing must still restore it
+        let undo = board.apply_with_undo(diff).unwrap();
+        board.unmake(undo);
+        assert_eq!(board.to_fen(), crate::START_FEN);
+
+        board.apply(diff).unwrap();
+        board.undo().unwrap();
+        assert_eq!(board.to_fen(), crate::START_FEN);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let board = RawBoard::default();
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(serde_json::from_str::<RawBoard>(&json).unwrap(), board);
+
+        let board = Board::new().board;
+        let json = serde_json::to_string(&board).unwrap();
+        assert!(json.starts_with(r#"{"data":[[["Rook","White"],["Knight","White"]"#));
+        assert_eq!(serde_json::from_str::<RawBoard>(&json).unwrap(), board);
+
+        let diff = Diff {
+            ty: DiffType::Capture { cap: pos!(3, 4) },
+            from: pos!(4, 3),
+            to: pos!(3, 4),
+        };
+        let json = serde_json::to_string(&diff).unwrap();
+        assert_eq!(
+            json,
+            r#"{"ty":{"Capture":{"cap":"d5"}},"from":"e4","to":"d5"}"#
+        );
+        assert_eq!(serde_json::from_str::<Diff>(&json).unwrap(), diff);
+
+        assert!(serde_json::from_str::<Pos>(r#""e9""#).is_err());
+    }
+
+    #[test]
+    fn threefold_repetition() {
+        let mut board = Board::new();
+
+        let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+
+        for (i, san) in shuffle.iter().chain(&shuffle).enumerate() {
+            assert!(!board.is_threefold_repetition());
+            board.apply(board.parse_san(san).unwrap()).unwrap();
+            assert_eq!(board.is_threefold_repetition(), i == 7);
+        }
+
+        board.undo().unwrap();
+        assert!(!board.is_threefold_repetition());
+    }
+
+    #[test]
+    fn threefold_repetition_castling() {
+        let mut board = make_board!(
+            ((4, 0) White King)
+            ((0, 0) White Rook)
+            ((4, 7) Black King)
+        );
+
+        let shuffle = ["Rb1", "Kd8", "Ra1", "Ke8"];
+
+        for san in shuffle.iter().chain(&shuffle) {
+            board.apply(board.parse_san(san).unwrap()).unwrap();
+        }
+
+        // the first position had castling rights, so it only occurred twice
+        assert!(!board.is_threefold_repetition());
+
+        for san in shuffle.iter() {
+            board.apply(board.parse_san(san).unwrap()).unwrap();
+        }
+
+        assert!(board.is_threefold_repetition());
+    }
+
+    #[test]
+    fn zobrist_hash_en_passant() {
+        let mut board = Board::new();
+        board.apply(board.parse_san("e4").unwrap()).unwrap();
+
+        // no black pawn can capture en passant, so it doesn't change the hash
+        let mut other = board.clone();
+        other.en_passant = None;
+        assert_eq!(board.zobrist_hash(), other.zobrist_hash());
+
+        let board = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1").unwrap();
+        let other = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K3 b - - 0 1").unwrap();
+        assert_ne!(board.zobrist_hash(), other.zobrist_hash());
+    }
+
+    #[test]
+    fn zobrist_hash_ignores_clocks() {
+        let board = Board::new();
+
+        let mut other = board.clone();
+        other.halfmove_clock = 37;
+        other.fullmove_number = 60;
+        assert_eq!(board.zobrist_hash(), other.zobrist_hash());
+
+        // the knights come back home, so only the clocks have changed
+        let mut other = board.clone();
+        for &san in &["Nf3", "Nf6", "Ng1", "Ng8"] {
+            other.make_move(other.parse_san(san).unwrap()).unwrap();
+        }
+        assert_eq!(other.halfmove_clock(), 4);
+        assert_eq!(other.fullmove_number(), 3);
+        assert_eq!(board.zobrist_hash(), other.zobrist_hash());
+    }
+
+    #[test]
+    fn fifty_move_rule() {
+        let mut board = Board::new();
+
+        let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+
+        for (i, san) in shuffle.iter().cycle().take(100).enumerate() {
+            assert_eq!(board.halfmove_clock(), i as u32);
+            assert!(!board.is_fifty_move_draw());
+            board.apply(board.parse_san(san).unwrap()).unwrap();
+        }
+
+        assert_eq!(board.halfmove_clock(), 100);
+        assert!(board.is_fifty_move_draw());
+
+        board.undo().unwrap();
+        assert_eq!(board.halfmove_clock(), 99);
+        assert!(!board.is_fifty_move_draw());
+
+        board.apply(board.parse_san("e5").unwrap()).unwrap();
+        assert_eq!(board.halfmove_clock(), 0);
+    }
+
+    #[test]
+    fn insufficient_material() {
+        let board = make_board!(
+            ((4, 0) White King)
+            ((4, 7) Black King)
+        );
+        assert!(board.is_insufficient_material());
+
+        let board = make_board!(
+            ((4, 0) White King)
+            ((2, 0) White Bishop)
+            ((4, 7) Black King)
+        );
+        assert!(board.is_insufficient_material());
+
+        let board = make_board!(
+            ((4, 0) White King)
+            ((4, 7) Black King)
+            ((6, 7) Black Knight)
+        );
+        assert!(board.is_insufficient_material());
+
+        let board = make_board!(
+            ((4, 0) White King)
+            ((2, 0) White Bishop)
+            ((4, 7) Black King)
+            ((5, 7) Black Bishop)
+        );
+        assert!(!pos!(2, 0).is_light() && !pos!(5, 7).is_light());
+        assert!(board.is_insufficient_material());
+
+        let board = make_board!(
+            ((4, 0) White King)
+            ((2, 0) White Bishop)
+            ((4, 7) Black King)
+            ((2, 7) Black Bishop)
+        );
+        assert!(pos!(2, 0).is_light() != pos!(2, 7).is_light());
+        assert!(!board.is_insufficient_material());
+
+        let board = make_board!(
+            ((4, 0) White King)
+            ((2, 0) White Bishop)
+            ((4, 7) Black King)
+            ((1, 7) Black Bishop)
+            ((5, 7) Black Bishop)
+        );
+        assert!(board.is_insufficient_material());
+
+        let board = make_board!(
+            ((4, 0) White King)
+            ((4, 7) Black King)
+            ((3, 3) Black Pawn)
+        );
+        assert!(!board.is_insufficient_material());
+
+        let board = make_board!(
+            ((4, 0) White King)
+            ((1, 0) White Knight)
+            ((6, 0) White Knight)
+            ((4, 7) Black King)
+        );
+        assert!(!board.is_insufficient_material());
+
+        assert!(!Board::new().is_insufficient_material());
+    }
+
+    #[test]
+    fn perft_start() {
+        let mut board = Board::new();
+
+        assert_eq!(board.perft(0), 1);
+        assert_eq!(board.perft(1), 20);
+        assert_eq!(board.perft(2), 400);
+        assert_eq!(board.perft(3), 8902);
+        assert!(board == Board::new());
+    }
+
+    #[test]
+    fn perft_start_depth_4() {
+        // deep enough that the make/unmake on the scratch copy in `get_possible_moves`
+        // runs on positions with checks, captures, and pins
+        let mut board = Board::new();
+
+        assert_eq!(board.perft(4), 197_281);
+        assert!(board == Board::new());
+    }
+
+    #[test]
+    fn perft_positions() {
+        // exercises castling, en passant, and promotions
+        let positions = [
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                48,
+                2039,
+            ),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 14, 191),
+            (
+                "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+                6,
+                264,
+            ),
+            (
+                "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                44,
+                1486,
+            ),
+        ];
+
+        for &(fen, depth_1, depth_2) in &positions {
+            let mut board = Board::from_fen(fen).unwrap();
+
+            assert_eq!(board.perft(1), depth_1, "{}", fen);
+            assert_eq!(board.perft(2), depth_2, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn all_legal_moves() {
+        let board = Board::new();
+
+        assert_eq!(board.all_legal_moves(Color::White).count(), 20);
+        assert_eq!(board.all_legal_moves(Color::Black).count(), 20);
+        assert!(board
+            .all_legal_moves(Color::White)
+            .all(|diff| board.get(diff.from).unwrap().1 == Color::White));
+    }
+
+    #[test]
+    fn evasions() {
+        use std::collections::HashSet;
+
+        let sets = |board: &Board| {
+            let evasions = board.evasions(Color::White).collect::<Vec<_>>();
+            let legal = board.all_legal_moves(Color::White).collect::<HashSet<_>>();
+
+            assert_eq!(evasions.len(), legal.len());
+            assert_eq!(evasions.iter().copied().collect::<HashSet<_>>(), legal);
+            evasions
+        };
+
+        // the rook on e8 checks the king, and can be captured or blocked
+        let board = Board::from_fen("4r1k1/8/3N4/1B6/8/7R/3P4/4K3 w - - 0 1").unwrap();
+        let evasions = sets(&board);
+
+        let has = |from, to| evasions.iter().any(|d| d.from == from && d.to == to);
+        assert!(has(Pos::D6, Pos::E8));
+        assert!(has(Pos::D6, Pos::E4));
+        assert!(has(Pos::B5, Pos::E8));
+        assert!(has(Pos::B5, Pos::E2));
+        assert!(has(Pos::H3, Pos::E3));
+        assert!(has(Pos::E1, Pos::D1));
+        assert!(!has(Pos::D2, Pos::D3));
+        assert!(!has(Pos::E1, Pos::E2));
+        assert_eq!(evasions.len(), 8);
+
+        // double check from the rook and the bishop, so only the king moves
+        let board = Board::from_fen("4r1k1/8/8/8/1b6/7R/8/4K3 w - - 0 1").unwrap();
+        let evasions = sets(&board);
+        assert!(evasions.iter().all(|diff| diff.from == Pos::E1));
+        assert_eq!(evasions.len(), 3);
+
+        // the pawn that just moved gives check, and is taken en passant
+        let board = Board::from_fen("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1").unwrap();
+        let evasions = board.evasions(Color::Black).collect::<Vec<_>>();
+        assert!(evasions
+            .iter()
+            .any(|diff| diff.from == Pos::E4 && diff.to == Pos::D3));
+        assert_eq!(evasions.len(), board.all_legal_moves(Color::Black).count());
+
+        let board = Board::new();
+        assert_eq!(sets(&board).len(), 20);
+    }
+
+    #[test]
+    fn checkers() {
+        assert!(Board::new().checkers(Color::White).is_empty());
+
+        let board = Board::from_fen("4r1k1/8/3N4/1B6/8/7R/3P4/4K3 w - - 0 1").unwrap();
+        assert_eq!(board.checkers(Color::White), [Pos::E8]);
+        assert!(board.checkers(Color::Black).is_empty());
+
+        // the knight moved from c5 to d3, uncovering the rook
+        let board = Board::from_fen("k3r3/8/8/8/8/3n4/8/4K3 w - - 0 1").unwrap();
+        let mut checkers = board.checkers(Color::White);
+        checkers.sort();
+
+        assert_eq!(checkers, [Pos::D3, Pos::E8]);
+        assert!(board
+            .evasions(Color::White)
+            .all(|diff| diff.from == Pos::E1));
+    }
+
+    #[test]
+    fn attackers_of() {
+        let board = make_board!(
+            ((3, 0) White Rook)
+            ((0, 0) White Bishop)
+            ((3, 7) White Rook)
+            ((0, 7) White Bishop)
+            ((4, 2) White Pawn)
+            ((3, 2) White Pawn)
+            ((5, 5) Black Bishop)
+            ((3, 4) Black Knight)
+        );
+
+        let sorted = |mut attackers: Vec<Pos>| {
+            attackers.sort_by_key(|&Pos(x, y)| (y, x));
+            attackers
+        };
+
+        // the rook on d1 is blocked by the pawn on d3
+        let attackers = sorted(board.attackers_of(pos!(3, 3), Color::White));
+        assert_eq!(attackers, [pos!(0, 0), pos!(4, 2)]);
+
+        let attackers = sorted(board.attackers_of(pos!(3, 4), Color::White));
+        assert_eq!(attackers, [pos!(0, 7), pos!(3, 7)]);
+
+        // defenders of a friendly piece
+        let attackers = board.attackers_of(pos!(3, 2), Color::White);
+        assert_eq!(attackers, [pos!(3, 0)]);
+
+        let attackers = board.attackers_of(pos!(3, 3), Color::Black);
+        assert_eq!(attackers, [pos!(5, 5)]);
+
+        let attackers = board.attackers_of(pos!(4, 2), Color::Black);
+        assert_eq!(attackers, [pos!(3, 4)]);
+    }
+
+    #[test]
+    fn is_square_attacked() {
+        let board = make_board!(
+            ((3, 3) White Pawn)
+            ((0, 0) White Rook)
+            ((7, 7) Black Bishop)
+            ((5, 5) Black Knight)
+        );
+
+        // pawns attack diagonally, even when there is nothing to capture
+        assert!(board.is_square_attacked(pos!(2, 4), Color::White));
+        assert!(board.is_square_attacked(pos!(4, 4), Color::White));
+        assert!(!board.is_square_attacked(pos!(3, 4), Color::White));
+        assert!(!board.is_square_attacked(pos!(2, 2), Color::White));
+
+        assert!(board.is_square_attacked(pos!(0, 7), Color::White));
+        assert!(board.is_square_attacked(pos!(7, 0), Color::White));
+        assert!(!board.is_square_attacked(pos!(1, 1), Color::White));
+
+        // the bishop is blocked by the knight
+        assert!(board.is_square_attacked(pos!(6, 6), Color::Black));
+        assert!(!board.is_square_attacked(pos!(4, 4), Color::Black));
+        assert!(board.is_square_attacked(pos!(3, 4), Color::Black));
+        assert!(board.is_square_attacked(pos!(4, 3), Color::Black));
+
+        // squares with pieces can be attacked too
+        assert!(board.is_square_attacked(pos!(5, 5), Color::Black));
+        assert!(!board.is_square_attacked(pos!(0, 0), Color::White));
+    }
+
+    #[test]
+    fn material() {
+        let mut board = Board::new();
+
+        assert_eq!(board.material(Color::White), 39);
+        assert_eq!(board.material(Color::Black), 39);
+
+        for san in &["e4", "d5", "exd5", "Qxd5"] {
+            board.apply(board.parse_san(san).unwrap()).unwrap();
+        }
+
+        assert_eq!(board.material(Color::White), 38);
+        assert_eq!(board.material(Color::Black), 38);
+    }
+
+    #[test]
+    fn material_balance() {
+        let mut board = Board::new();
+        assert_eq!(board.material_balance(), 0);
+
+        board.board.clear(Pos::D8);
+        assert_eq!(board.material_balance(), 9);
+
+        board.board.clear(Pos::A1);
+        board.board.clear(Pos::B1);
+        assert_eq!(board.material_balance(), 1);
+    }
+
+    #[test]
+    fn empty() {
+        let board = Board::empty();
 
-        let is_king_check = self.is_king_check(color);
+        assert_eq!(board.pieces(Color::White).count(), 0);
+        assert_eq!(board.pieces(Color::Black).count(), 0);
+        assert_eq!(board.to_fen(), "8/8/8/8/8/8/8/8 w - - 0 1");
+        assert_eq!(board.find_king(Color::White), None);
 
-        match (is_king_check, has_moves) {
-            (true, true) => GameCondition::Check,
-            (true, false) => GameCondition::Mate,
-            (false, true) => GameCondition::Safe,
-            (false, false) => GameCondition::Stale,
-        }
+        let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K2R w K d6 7 30").unwrap();
+        let empty = board.clone_empty();
+
+        assert_eq!(empty.to_fen(), "8/8/8/8/8/8/8/8 w - - 7 30");
+
+        let board = Board::new_chess960(0);
+        assert!(board.clone_empty().castle_files == board.castle_files);
+        assert!(board.castle_files != CastleFiles::STANDARD);
     }
-}
 
-impl Default for Board {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn find_king() {
+        let board = Board::new();
+
+        assert_eq!(board.find_king(Color::White), Some(Pos::E1));
+        assert_eq!(board.find_king(Color::Black), Some(Pos::E8));
+
+        let board = make_board!(
+            ((2, 5) Black King)
+        );
+
+        assert_eq!(board.find_king(Color::White), None);
+        assert_eq!(board.find_king(Color::Black), Some(Pos::C6));
     }
-}
 
-mod fmt {
-    use super::*;
-    use std::fmt;
+    #[test]
+    fn king_cache() {
+        let mut board = Board::new();
+        assert_eq!(board.white_king, Some(pos!(4, 0)));
+        assert_eq!(board.black_king, Some(pos!(4, 7)));
 
-    impl fmt::Debug for Board {
-        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            for col in self.board.data.iter().rev() {
-                for &tile in col {
-                    match tile {
-                        Some((pt, _)) => write!(f, "{}. ", pt.get_ident())?,
-                        None => write!(f, "__ ")?,
-                    }
-                }
+        for &(from, to) in &[
+            (pos!(4, 1), pos!(4, 3)),
+            (pos!(4, 6), pos!(4, 4)),
+            (pos!(4, 0), pos!(4, 1)),
+        ] {
+            let diff = board
+                .get_possible_moves(from)
+                .unwrap()
+                .find(|diff| diff.to == to)
+                .unwrap();
+            board.apply(diff).unwrap();
+        }
 
-                writeln!(f)?;
+        assert_eq!(board.white_king, Some(pos!(4, 1)));
+        assert!(board.game_condition(Color::White) == GameCondition::Safe);
 
-                for &tile in col {
-                    match tile {
-                        Some((_, color)) => write!(f, ".{} ", color.get_ident())?,
-                        None => write!(f, "__ ")?,
-                    }
-                }
+        board.undo().unwrap();
+        assert_eq!(board.white_king, Some(pos!(4, 0)));
 
-                writeln!(f)?;
+        let mut board = make_board!(
+            ((4, 0) White King)
+            ((7, 0) White Rook)
+            ((2, 5) Black King)
+        );
+        assert_eq!(board.black_king, Some(pos!(2, 5)));
+
+        let castle = board
+            .get_possible_moves(pos!(4, 0))
+            .unwrap()
+            .find(|diff| matches!(diff.ty, DiffType::Castle { .. }))
+            .unwrap();
+        let undo = board.apply_with_undo(castle).unwrap();
+        assert_eq!(board.white_king, Some(pos!(6, 0)));
+
+        board.unmake(undo);
+        assert_eq!(board.white_king, Some(pos!(4, 0)));
+        assert_eq!(make_board!().white_king, None);
+    }
+
+    #[test]
+    fn legal_moves_into() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+
+        let mut buf = Vec::new();
+        let mut total = 0;
+
+        for pos in Pos::all() {
+            board.legal_moves_into(pos, &mut buf);
+
+            if let Ok((_, Color::White)) = board.get(pos) {
+                total += buf.len();
             }
 
-            Ok(())
+            let expected = board
+                .get_possible_moves(pos)
+                .map_or_else(Vec::new, Iterator::collect);
+            assert_eq!(buf, expected);
         }
-    }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::board::*;
+        assert_eq!(total, 48);
 
-    macro_rules! pos {
-        ($x:expr, $y:expr) => {
-            Pos::new_unchecked($x, $y)
-        };
+        board.legal_moves_into(pos!(4, 0), &mut buf);
+        let first = buf.len();
+        buf.clear();
+        board.legal_moves_into(pos!(4, 0), &mut buf);
+        assert_eq!(buf.len(), first);
+        board.legal_moves_into(pos!(4, 0), &mut buf);
+        assert_eq!(buf.len(), first);
+
+        board.legal_moves_into(pos!(4, 3), &mut buf);
+        assert!(buf.is_empty());
     }
 
-    macro_rules! poss_move_u {
-        ($board:expr, $x:expr, $y:expr) => {
-            $board
-                .get_possible_moves_unchecked(pos!($x, $y))
-                .unwrap()
-                .collect::<Vec<_>>()
-        };
+    #[test]
+    fn capture_moves() {
+        let board = Board::new();
+
+        for x in 0..8 {
+            assert_eq!(board.capture_moves(pos!(x, 1)).unwrap().count(), 0);
+        }
+
+        let board = make_board!(
+            ((4, 3) White Pawn)
+            ((3, 4) Black Pawn)
+            ((5, 4) Black Pawn)
+            ((4, 4) Black Knight)
+        );
+
+        let captures = board.capture_moves(pos!(4, 3)).unwrap().collect::<Vec<_>>();
+        assert_eq!(captures.len(), 2);
+        assert!(captures
+            .iter()
+            .all(|diff| matches!(diff.ty, DiffType::Capture { .. })));
     }
 
-    macro_rules! make_board {
-        (
-            $(($($rest:tt)*))*
-        ) => {{
-            #[allow(unused_mut)]
-            let mut board = RawBoard { data: [[None; 8]; 8] };
+    #[test]
+    fn diff_accessors() {
+        let board = Board::new();
 
-            $(
-                make_board!(@internal board $($rest)*);
-            )*
+        let diff = board
+            .get_possible_moves(pos!(6, 0))
+            .unwrap()
+            .find(|diff| diff.to() == pos!(5, 2))
+            .unwrap();
 
-            dbg!(Board::with(board))
-        }};
+        assert_eq!(diff.from(), pos!(6, 0));
+        assert_eq!(diff.to(), pos!(5, 2));
+        assert_eq!(diff.kind(), DiffType::Move);
+    }
 
-        (@internal $board:ident ($x:expr, $y:expr) $color:ident $piece:ident) => {
-            $board.set(Pos($x, $y), PieceType::$piece, Color::$color);
+    #[test]
+    fn diff_predicates() {
+        let diff = |ty| Diff {
+            ty,
+            from: pos!(4, 6),
+            to: pos!(4, 7),
         };
+
+        let cases = [
+            (DiffType::Move, false, false),
+            (DiffType::Capture { cap: pos!(4, 7) }, true, false),
+            (
+                DiffType::EnPassant {
+                    captured: pos!(4, 6),
+                },
+                true,
+                false,
+            ),
+            (
+                DiffType::Castle {
+                    rook_from: pos!(7, 7),
+                    rook_to: pos!(5, 7),
+                },
+                false,
+                false,
+            ),
+            (
+                DiffType::Promote {
+                    piece: PieceType::Queen,
+                    capture: false,
+                },
+                false,
+                true,
+            ),
+            (
+                DiffType::Promote {
+                    piece: PieceType::Knight,
+                    capture: true,
+                },
+                true,
+                true,
+            ),
+        ];
+
+        for &(ty, capture, promotion) in cases.iter() {
+            assert_eq!(diff(ty).is_capture(), capture, "{:?}", ty);
+            assert_eq!(diff(ty).is_promotion(), promotion, "{:?}", ty);
+        }
     }
 
     #[test]
-    fn gpmu_pass_1() {
-        let board = Board::new();
+    fn with_validated() {
+        let valid = make_board!(
+            ((4, 0) White King)
+            ((4, 1) White Pawn)
+            ((4, 7) Black King)
+        );
+        assert!(Board::with_validated(valid.board, Color::White).is_ok());
 
-        let moves = poss_move_u!(board, 0, 1);
+        let no_king = make_board!(
+            ((4, 0) White King)
+        );
+        assert_eq!(
+            Board::with_validated(no_king.board, Color::White).unwrap_err(),
+            PositionError::MissingKing(Color::Black)
+        );
 
-        assert_eq!(moves.len(), 2);
-        assert!(moves.contains(&Diff {
-            ty: DiffType::Move,
-            from: pos!(0, 1),
-            to: pos!(0, 2)
-        }));
-        assert!(moves.contains(&Diff {
-            ty: DiffType::Move,
-            from: pos!(0, 1),
-            to: pos!(0, 3)
-        }));
+        let two_kings = make_board!(
+            ((4, 0) White King)
+            ((0, 0) White King)
+            ((4, 7) Black King)
+        );
+        assert_eq!(
+            Board::with_validated(two_kings.board, Color::White).unwrap_err(),
+            PositionError::TooManyKings(Color::White)
+        );
+
+        let back_rank_pawn = make_board!(
+            ((4, 0) White King)
+            ((4, 7) Black King)
+            ((2, 7) White Pawn)
+        );
+        assert_eq!(
+            Board::with_validated(back_rank_pawn.board, Color::White).unwrap_err(),
+            PositionError::PawnOnBackRank(pos!(2, 7))
+        );
+
+        let in_check = make_board!(
+            ((4, 0) White King)
+            ((4, 7) Black King)
+            ((4, 3) White Rook)
+        );
+        assert_eq!(
+            Board::with_validated(in_check.board, Color::White).unwrap_err(),
+            PositionError::OpponentInCheck
+        );
+        assert!(Board::with_validated(in_check.board, Color::Black).is_ok());
     }
 
     #[test]
-    fn gpmu_pass_2() {
-        let board = Board::new();
+    fn pinned_pieces() {
+        let board = make_board!(
+            ((4, 0) White King)
+            ((4, 2) White Knight)
+            ((4, 6) Black Rook)
+            ((1, 3) White Bishop)
+            ((0, 4) Black Queen)
+            ((6, 2) White Pawn)
+            ((7, 3) Black Knight)
+            ((2, 0) White Rook)
+            ((0, 0) Black Bishop)
+            ((7, 7) Black King)
+        );
 
-        let moves = poss_move_u!(board, 1, 0);
+        let pins = board.pinned_pieces(Color::White);
 
-        assert_eq!(moves.len(), 2);
-        assert!(moves.contains(&Diff {
-            ty: DiffType::Move,
-            from: pos!(1, 0),
-            to: pos!(0, 2)
-        }));
-        assert!(moves.contains(&Diff {
-            ty: DiffType::Move,
-            from: pos!(1, 0),
-            to: pos!(2, 2)
-        }));
+        // the knight is pinned by the rook, and the bishop by the queen,
+        // but the pawn isn't pinned by a knight, and a bishop can't pin along a rank
+        assert_eq!(pins.len(), 2);
+        assert!(pins.contains(&(pos!(4, 2), Vector { x: 0, y: -1 })));
+        assert!(pins.contains(&(pos!(1, 3), Vector { x: 1, y: -1 })));
+
+        assert!(board.pinned_pieces(Color::Black).is_empty());
     }
 
     #[test]
-    fn gpmu_pass_3() {
+    fn index() {
         let board = Board::new();
 
-        let moves = poss_move_u!(board, 0, 0);
-        assert!(moves.is_empty());
+        assert_eq!(board[pos!(4, 0)], Some((PieceType::King, Color::White)));
+        assert_eq!(board[pos!(3, 6)], Some((PieceType::Pawn, Color::Black)));
+        assert_eq!(board[pos!(4, 3)], None);
+    }
 
-        let moves = poss_move_u!(board, 2, 0);
-        assert!(moves.is_empty());
+    #[test]
+    fn error_positions() {
+        let mut board = Board::new();
 
-        let moves = poss_move_u!(board, 3, 0);
-        assert!(moves.is_empty());
+        let err = board.apply(Diff {
+            ty: DiffType::Move,
+            from: pos!(4, 3),
+            to: pos!(4, 4),
+        });
 
-        let moves = poss_move_u!(board, 4, 0);
-        assert!(moves.is_empty());
+        match err {
+            Err(Error::NoPiece(pos)) => assert_eq!(pos, pos!(4, 3)),
+            err => panic!("{:?}", err),
+        }
+
+        assert_eq!(Pos::new(8, 2).unwrap_err(), OutOfBounds { x: 8, y: 2 });
+        assert_eq!(
+            Pos::try_from(Vector { x: -1, y: 3 }).unwrap_err(),
+            OutOfBounds { x: -1, y: 3 }
+        );
     }
 
     #[test]
@@ -475,14 +4080,6 @@ mod test {
 
     #[test]
     fn gc_pass_3() {
-        let mut board = RawBoard {
-            data: [[None; 8]; 8],
-        };
-
-        board.data[0][0] = Some((PieceType::King, Color::White));
-        board.data[1][0] = Some((PieceType::Pawn, Color::Black));
-        board.data[7][0] = Some((PieceType::Rook, Color::Black));
-
         let board = make_board!(
             ((0, 0) White King)
             ((0, 1) Black Pawn)
@@ -548,4 +4145,226 @@ mod test {
 
         assert_eq!(board.game_condition(Color::White), GameCondition::Check);
     }
+
+    #[test]
+    fn raw_board_place() {
+        let mut raw = RawBoard::default();
+
+        raw.place(Pos::E1, PieceType::King, Color::White);
+        raw.place(Pos::E4, PieceType::Pawn, Color::White);
+        raw.place(Pos::E8, PieceType::King, Color::Black);
+        raw.place(Pos::D7, PieceType::Queen, Color::Black);
+
+        assert_eq!(raw.clear(Pos::D7), Some((PieceType::Queen, Color::Black)));
+        assert_eq!(raw.clear(Pos::D7), None);
+
+        let board = Board::with(raw);
+        assert_eq!(board.to_fen(), "4k3/8/8/8/4P3/8/8/4K3 w - - 0 1");
+        assert_eq!(board.find_king(Color::Black), Some(Pos::E8));
+    }
+
+    #[test]
+    fn raw_board_transforms() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap()
+                .board;
+
+        assert_eq!(board.rotate_180().rotate_180(), board);
+        assert_eq!(board.mirror_files().mirror_files(), board);
+        assert_ne!(board.rotate_180(), board);
+
+        let mut raw = RawBoard::default();
+        raw.place(Pos::A1, PieceType::Rook, Color::White);
+        raw.place(Pos::C2, PieceType::Pawn, Color::Black);
+
+        let rotated = raw.rotate_180();
+        assert_eq!(
+            rotated.get(Pos::H8).ok(),
+            Some((PieceType::Rook, Color::White))
+        );
+        assert_eq!(
+            rotated.get(Pos::F7).ok(),
+            Some((PieceType::Pawn, Color::Black))
+        );
+        assert_eq!(rotated.iter().count(), 2);
+
+        let mirrored = raw.mirror_files();
+        assert_eq!(
+            mirrored.get(Pos::H1).ok(),
+            Some((PieceType::Rook, Color::White))
+        );
+        assert_eq!(
+            mirrored.get(Pos::F2).ok(),
+            Some((PieceType::Pawn, Color::Black))
+        );
+        assert_eq!(mirrored.iter().count(), 2);
+    }
+
+    #[test]
+    fn census() {
+        let board = Board::new().board;
+
+        for &color in &[Color::White, Color::Black] {
+            assert_eq!(board.count(PieceType::Pawn, color), 8);
+            assert_eq!(board.count(PieceType::Knight, color), 2);
+            assert_eq!(board.count(PieceType::King, color), 1);
+        }
+
+        let census = board.census();
+        assert_eq!(census[Color::White as usize], [1, 1, 2, 2, 2, 8]);
+        assert_eq!(census[Color::White as usize], census[Color::Black as usize]);
+
+        let board = make_board!(
+            ((4, 0) White King)
+            ((3, 3) Black Queen)
+        )
+        .board;
+
+        assert_eq!(board.count(PieceType::Queen, Color::Black), 1);
+        assert_eq!(board.count(PieceType::Queen, Color::White), 0);
+        assert_eq!(board.count(PieceType::Pawn, Color::White), 0);
+    }
+
+    #[test]
+    fn into_iter() {
+        let board = Board::new();
+        let mut counts = [0; 2];
+        let mut material = 0;
+
+        for (_, piece, color) in &board {
+            counts[color as usize] += 1;
+            material += piece.value();
+        }
+
+        assert_eq!(counts, [16, 16]);
+        assert_eq!(material, 78);
+
+        let squares = (&board.board).into_iter().map(|(pos, _, _)| pos);
+        assert!(squares.eq(Pos::all().filter(|&Pos(_, y)| !(2..=5).contains(&y))));
+    }
+
+    #[test]
+    fn occupancy() {
+        let board = Board::new().board;
+
+        assert_eq!(board.occupancy().count_ones(), 32);
+        assert_eq!(board.occupancy_of(Color::White), 0x0000_0000_0000_ffff);
+        assert_eq!(board.occupancy_of(Color::Black), 0xffff_0000_0000_0000);
+
+        let board = make_board!(
+            ((4, 3) White Pawn)
+        )
+        .board;
+
+        assert_eq!(board.occupancy(), 1 << 28);
+        assert_eq!(board.occupancy_of(Color::Black), 0);
+    }
+
+    #[test]
+    fn pieces() {
+        let board = Board::new();
+
+        assert_eq!(board.pieces(Color::White).count(), 16);
+        assert_eq!(board.pieces(Color::Black).count(), 16);
+        assert!(board
+            .pieces(Color::White)
+            .all(|(pos, piece)| board.get(pos).ok() == Some((piece, Color::White))));
+        assert!(board.pieces(Color::Black).all(|(Pos(_, y), _)| y >= 6));
+    }
+
+    #[test]
+    fn legal_move_count() {
+        let board = Board::new();
+
+        assert_eq!(board.legal_move_count(Color::White), 20);
+        assert_eq!(board.legal_move_count(Color::Black), 20);
+
+        let board = make_board!(
+            ((0, 0) White King)
+            ((0, 7) Black Rook)
+            ((1, 7) Black Queen)
+        );
+
+        assert_eq!(board.legal_move_count(Color::White), 0);
+    }
+
+    #[test]
+    fn checkmate_and_stalemate() {
+        let board = Board::new();
+        assert!(!board.is_checkmate());
+        assert!(!board.is_stalemate());
+
+        let board = make_board!(
+            ((0, 0) White King)
+            ((0, 7) Black Rook)
+            ((1, 7) Black Queen)
+        );
+        assert!(board.is_checkmate());
+        assert!(!board.is_stalemate());
+
+        let board = make_board!(
+            ((0, 0) White King)
+            ((1, 2) Black Queen)
+            ((7, 7) Black King)
+        );
+        assert!(!board.is_checkmate());
+        assert!(board.is_stalemate());
+    }
+
+    #[test]
+    fn result() {
+        assert_eq!(Board::new().result(), GameResult::Ongoing);
+
+        let board = make_board!(
+            ((6, 0) White King)
+            ((5, 1) White Pawn)
+            ((6, 1) White Pawn)
+            ((7, 1) White Pawn)
+            ((0, 0) Black Rook)
+            ((6, 7) Black King)
+        );
+
+        assert_eq!(board.to_move(), Color::White);
+        assert_eq!(board.result(), GameResult::BlackWins);
+
+        let board = Board::with_turn(
+            make_board!(
+                ((0, 7) Black King)
+                ((1, 5) White Queen)
+                ((7, 0) White King)
+            )
+            .board,
+            Color::Black,
+        );
+
+        assert_eq!(board.result(), GameResult::Draw);
+
+        let board = make_board!(
+            ((0, 0) White King)
+            ((7, 7) Black King)
+        );
+
+        assert_eq!(board.result(), GameResult::Draw);
+    }
+
+    #[test]
+    fn is_draw() {
+        assert!(!Board::new().is_draw());
+
+        let stalemate = Board::from_fen("k7/8/1Q6/8/8/8/8/7K b - - 0 1").unwrap();
+        assert!(stalemate.is_draw());
+
+        let fifty = Board::from_fen("k7/8/8/8/8/8/8/R6K b - - 100 80").unwrap();
+        assert!(fifty.is_draw());
+
+        // mate on the hundredth halfmove still wins
+        let mate = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 100 80").unwrap();
+        assert!(mate.is_checkmate());
+        assert!(!mate.is_draw());
+
+        assert!(Board::from_fen("k7/8/8/8/8/8/8/6NK w - - 0 1")
+            .unwrap()
+            .is_draw());
+    }
 }
@@ -0,0 +1,133 @@
+use crate::board::{Board, CastlingRights, Pos, RawBoard};
+use crate::error::PositionError;
+use crate::pieces::{Color, Piece};
+
+/**
+ * Sets up a custom position one piece at a time, i.e.
+ * `BoardBuilder::empty().place(Pos::E1, Piece::King, Color::White).turn(Color::Black).build()`
+ */
+#[derive(Debug, Clone)]
+pub struct BoardBuilder {
+    board: RawBoard,
+    turn: Color,
+    castling: Option<CastlingRights>,
+}
+
+impl BoardBuilder {
+    /**
+     * an empty board with white to move
+     */
+    pub fn empty() -> Self {
+        Self {
+            board: RawBoard::default(),
+            turn: Color::White,
+            castling: None,
+        }
+    }
+
+    pub fn place(mut self, pos: Pos, piece: Piece, color: Color) -> Self {
+        self.board.place(pos, piece, color);
+        self
+    }
+
+    pub fn turn(mut self, turn: Color) -> Self {
+        self.turn = turn;
+        self
+    }
+
+    /**
+     * by default the castling rights are inferred from which kings and rooks
+     * are on their starting squares, and any rights given here that those
+     * pieces don't allow are dropped
+     */
+    pub fn castling(mut self, castling: CastlingRights) -> Self {
+        self.castling = Some(castling);
+        self
+    }
+
+    /**
+     * checks the position with `Board::with_validated`, and creates the board
+     */
+    pub fn build(self) -> Result<Board, PositionError> {
+        let mut board = Board::with_validated(self.board, self.turn)?;
+
+        if let Some(castling) = self.castling {
+            let inferred = board.castling;
+            board.castling = CastlingRights::none();
+
+            let rights = [
+                CastlingRights::WHITE_KINGSIDE,
+                CastlingRights::WHITE_QUEENSIDE,
+                CastlingRights::BLACK_KINGSIDE,
+                CastlingRights::BLACK_QUEENSIDE,
+            ];
+
+            for &right in &rights {
+                if castling.contains(right) && inferred.contains(right) {
+                    board.castling.insert(right);
+                }
+            }
+        }
+
+        Ok(board)
+    }
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn build_endgame() {
+        let board = BoardBuilder::empty()
+            .place(Pos::E1, Piece::King, Color::White)
+            .place(Pos::H1, Piece::Rook, Color::White)
+            .place(Pos::E5, Piece::Pawn, Color::White)
+            .place(Pos::E8, Piece::King, Color::Black)
+            .place(Pos::A8, Piece::Rook, Color::Black)
+            .turn(Color::Black)
+            .build()
+            .unwrap();
+
+        assert_eq!(board.to_fen(), "r3k3/8/8/4P3/8/8/8/4K2R b Kq - 0 1");
+    }
+
+    #[test]
+    fn build_castling() {
+        let builder = BoardBuilder::empty()
+            .place(Pos::E1, Piece::King, Color::White)
+            .place(Pos::H1, Piece::Rook, Color::White)
+            .place(Pos::E8, Piece::King, Color::Black)
+            .place(Pos::A8, Piece::Rook, Color::Black);
+
+        let board = builder
+            .clone()
+            .castling(CastlingRights::none())
+            .build()
+            .unwrap();
+        assert_eq!(board.castling_rights(), CastlingRights::none());
+
+        // white has no queenside rook, so that right is dropped
+        let mut rights = CastlingRights::WHITE_KINGSIDE;
+        rights.insert(CastlingRights::WHITE_QUEENSIDE);
+
+        let board = builder.castling(rights).build().unwrap();
+        assert_eq!(board.castling_rights(), CastlingRights::WHITE_KINGSIDE);
+    }
+
+    #[test]
+    fn build_invalid() {
+        let err = BoardBuilder::empty()
+            .place(Pos::E1, Piece::King, Color::White)
+            .build()
+            .unwrap_err();
+
+        assert_eq!(err, PositionError::MissingKing(Color::Black));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::board::{Board, CastleFiles, CastlingRights, Pos, RawBoard};
+use crate::pieces::{Color, Piece};
+use alloc::vec::Vec;
+
+/**
+ * where the two knights go among the five squares left after placing
+ * the bishops and the queen, indexed by the last digit of the Scharnagl number
+ */
+const KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+/**
+ * the back rank for the given Scharnagl number, from the a file to the h file
+ */
+fn back_rank(position_id: u16) -> [Piece; 8] {
+    let mut rank = [None; 8];
+    let mut n = position_id as usize;
+
+    // the light squared bishop goes on b, d, f or h
+    rank[n % 4 * 2 + 1] = Some(Piece::Bishop);
+    n /= 4;
+
+    // the dark squared bishop goes on a, c, e or g
+    rank[n % 4 * 2] = Some(Piece::Bishop);
+    n /= 4;
+
+    let empty =
+        |rank: &[Option<Piece>; 8]| (0..8).filter(|&x| rank[x].is_none()).collect::<Vec<_>>();
+
+    rank[empty(&rank)[n % 6]] = Some(Piece::Queen);
+    n /= 6;
+
+    let (a, b) = KNIGHTS[n];
+    let squares = empty(&rank);
+    rank[squares[a]] = Some(Piece::Knight);
+    rank[squares[b]] = Some(Piece::Knight);
+
+    // the king always goes between the two rooks
+    let squares = empty(&rank);
+    rank[squares[0]] = Some(Piece::Rook);
+    rank[squares[1]] = Some(Piece::King);
+    rank[squares[2]] = Some(Piece::Rook);
+
+    let mut pieces = [Piece::Pawn; 8];
+    for (piece, square) in pieces.iter_mut().zip(rank.iter()) {
+        *piece = square.unwrap();
+    }
+
+    pieces
+}
+
+impl Board {
+    /**
+     * Creates a Chess960 starting position from its Scharnagl number (0 to 959),
+     * where 518 is the standard starting position
+     *
+     * Black's pieces mirror White's, and both sides may castle with the rooks
+     * on whichever files they start on
+     *
+     * # Panics
+     *
+     * If `position_id` is greater than 959
+     */
+    pub fn new_chess960(position_id: u16) -> Self {
+        assert!(
+            position_id < 960,
+            "Chess960 positions are numbered from 0 to 959, got {}",
+            position_id
+        );
+
+        let pieces = back_rank(position_id);
+        let mut board = RawBoard::default();
+
+        for (x, &piece) in pieces.iter().enumerate() {
+            board.set(Pos(x, 0), piece, Color::White);
+            board.set(Pos(x, 1), Piece::Pawn, Color::White);
+            board.set(Pos(x, 6), Piece::Pawn, Color::Black);
+            board.set(Pos(x, 7), piece, Color::Black);
+        }
+
+        let file_of = |target, skip| (0..8).filter(|&x| pieces[x] == target).nth(skip).unwrap();
+
+        let mut board = Board::with(board);
+        board.castling = CastlingRights::ALL;
+        board.castle_files = CastleFiles {
+            king: file_of(Piece::King, 0),
+            queenside: file_of(Piece::Rook, 0),
+            kingside: file_of(Piece::Rook, 1),
+        };
+
+        board
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn standard() {
+        assert!(Board::new_chess960(518) == Board::new());
+    }
+
+    #[test]
+    fn back_ranks() {
+        assert_eq!(
+            Board::new_chess960(0).to_fen(),
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1"
+        );
+        assert_eq!(
+            Board::new_chess960(959).to_fen(),
+            "rkrnnqbb/pppppppp/8/8/8/8/PPPPPPPP/RKRNNQBB w KQkq - 0 1"
+        );
+
+        for id in 0..960 {
+            let board = Board::new_chess960(id);
+            let rank = (0..8)
+                .map(|x| board.get(Pos::new(x, 0).unwrap()).unwrap())
+                .collect::<Vec<_>>();
+
+            let bishops = (0..8)
+                .filter(|&x| rank[x].0 == Piece::Bishop)
+                .collect::<Vec<_>>();
+            assert_eq!(bishops.len(), 2);
+            assert_ne!(bishops[0] % 2, bishops[1] % 2);
+        }
+    }
+
+    #[test]
+    fn castling() {
+        assert_eq!(
+            Board::new_chess960(959).castling_rights(),
+            CastlingRights::ALL
+        );
+
+        // the king starts on c1, with the rooks on b1 and h1
+        let mut board = Board::from_ascii(
+            "\
+....k...
+........
+........
+........
+........
+........
+........
+.RK....R
+",
+        )
+        .unwrap();
+        board.castle_files = crate::board::CastleFiles {
+            king: 2,
+            queenside: 1,
+            kingside: 7,
+        };
+        board.castling.insert(CastlingRights::WHITE_KINGSIDE);
+        board.castling.insert(CastlingRights::WHITE_QUEENSIDE);
+
+        let castles = board
+            .get_possible_moves("c1".parse().unwrap())
+            .unwrap()
+            .filter(|diff| matches!(diff.kind(), DiffType::Castle { .. }))
+            .collect::<Vec<_>>();
+        assert_eq!(castles.len(), 2);
+
+        let sans = castles
+            .iter()
+            .map(|&diff| board.to_san(diff))
+            .collect::<Vec<_>>();
+        assert_eq!(sans, ["O-O", "O-O-O"]);
+
+        let mut after = board.clone();
+        after.make_move(castles[0]).unwrap();
+        assert_eq!(after.to_ascii().lines().last(), Some(".R...RK."));
+        assert_eq!(after.castling_rights(), CastlingRights::none());
+
+        // the king is already on the c file, so only the rook moves
+        let mut after = board.clone();
+        after.make_move(castles[1]).unwrap();
+        assert_eq!(after.to_ascii().lines().last(), Some("..KR...R"));
+
+        after.undo().unwrap();
+        assert!(after == board);
+    }
+}
@@ -0,0 +1,109 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::board::Board;
+use crate::error::EpdError;
+
+/**
+ * The operations of an EPD record, from each opcode (i.e. `bm` or `id`)
+ * to its operands, with the quotes around string operands removed
+ */
+pub type EpdOps = BTreeMap<String, Vec<String>>;
+
+/**
+ * Parses a position in Extended Position Description
+ *
+ * This is the first four fields of FEN, without the halfmove clock or fullmove
+ * number (which default to 0 and 1), followed by operations like `bm Nf3;` or
+ * `id "test 1";`. Each operation is an opcode and its operands, ended by a `;`
+ */
+pub fn from_epd(epd: &str) -> Result<(Board, EpdOps), EpdError> {
+    let mut rest = epd.trim_start();
+    let mut fields = Vec::new();
+
+    for _ in 0..4 {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        fields.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+
+    let board = Board::from_fen(&fields.join(" ")).map_err(EpdError::Fen)?;
+    let mut ops = EpdOps::new();
+
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == ';')
+            .unwrap_or(rest.len());
+        let opcode = &rest[..end];
+        rest = rest[end..].trim_start();
+
+        let mut operands = Vec::new();
+
+        loop {
+            match rest.chars().next() {
+                None => return Err(EpdError::UnterminatedOperation),
+                Some(';') => {
+                    rest = rest[1..].trim_start();
+                    break;
+                }
+                Some('"') => {
+                    let end = rest[1..].find('"').ok_or(EpdError::UnterminatedString)?;
+                    operands.push(rest[1..=end].to_string());
+                    rest = rest[end + 2..].trim_start();
+                }
+                Some(_) => {
+                    let end = rest
+                        .find(|c: char| c.is_whitespace() || c == ';')
+                        .unwrap_or(rest.len());
+                    operands.push(rest[..end].to_string());
+                    rest = rest[end..].trim_start();
+                }
+            }
+        }
+
+        ops.insert(opcode.to_string(), operands);
+    }
+
+    Ok((board, ops))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn from_epd_ops() {
+        let epd = r#"r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - bm Bb5 Bc4; id "open game; move 3";"#;
+        let (board, ops) = from_epd(epd).unwrap();
+
+        assert_eq!(
+            board.to_fen(),
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 1"
+        );
+        assert_eq!(ops["bm"], ["Bb5", "Bc4"]);
+        assert_eq!(ops["id"], ["open game; move 3"]);
+        assert_eq!(ops.len(), 2);
+
+        for san in &ops["bm"] {
+            assert!(board.parse_san(san).is_ok());
+        }
+    }
+
+    #[test]
+    fn from_epd_errors() {
+        assert_eq!(
+            from_epd("8/8/8/8/8/8/8/8 w").unwrap_err(),
+            EpdError::Fen(FenError::MissingField)
+        );
+
+        let epd = "4k3/8/8/8/8/8/8/4K3 w - - bm Kd2";
+        assert_eq!(from_epd(epd).unwrap_err(), EpdError::UnterminatedOperation);
+
+        let epd = r#"4k3/8/8/8/8/8/8/4K3 w - - id "never closed;"#;
+        assert_eq!(from_epd(epd).unwrap_err(), EpdError::UnterminatedString);
+
+        let (_, ops) = from_epd("4k3/8/8/8/8/8/8/4K3 w - -").unwrap();
+        assert!(ops.is_empty());
+    }
+}
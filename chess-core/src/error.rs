@@ -1,5 +1,16 @@
-#[derive(Debug)]
-pub struct OutOfBounds;
+use crate::board::Pos;
+use crate::pieces::Color;
+
+/// Tried to make a position outside of the board
+#[derive(Debug, PartialEq, Eq)]
+pub struct OutOfBounds {
+    pub x: i64,
+    pub y: i64,
+}
+
+/// A square wasn't a file `a`-`h` followed by a rank `1`-`8`
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsePosError;
 
 #[derive(Debug)]
 pub enum InvalidDiff {
@@ -11,18 +22,123 @@ pub enum InvalidDiff {
     InvalidPromotionPiece,
     /// Tried to promote from the wrong row
     InvalidPromotionRow,
+    /// Tried to promote to a king or a pawn
+    InvalidPromotionTarget,
+    /// Tried to castle with something other than a king and rook of the same color
+    InvalidCastlePieces,
+    /// Tried to capture en passant with or against something other than a pawn
+    InvalidEnPassantPiece,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FenError {
+    /// The FEN string is missing one of the required fields
+    MissingField,
+    /// The FEN string has more than six fields
+    TooManyFields,
+    /// The piece placement doesn't have exactly eight ranks
+    InvalidRankCount(usize),
+    /// The given rank (1 to 8) doesn't describe exactly eight squares
+    InvalidRankLength(usize),
+    /// Found a character that isn't a piece or a run of empty squares
+    UnknownPiece(char),
+    /// The active color isn't `w` or `b`
+    InvalidColor,
+    /// Found a character that isn't one of `KQkq` in the castling rights
+    InvalidCastling(char),
+    /// The en passant target isn't `-` or a square on the third or sixth rank
+    InvalidEnPassant,
+    /// The halfmove clock or fullmove number isn't a valid number
+    InvalidCounter,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The diagram doesn't have exactly eight ranks
+    InvalidRankCount(usize),
+    /// The given rank (1 to 8) isn't exactly eight squares wide
+    InvalidRankLength(usize),
+    /// Found a character that isn't a piece, `.`, or a space
+    UnknownPiece(char),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PositionError {
+    /// The given side has no king
+    MissingKing(Color),
+    /// The given side has more than one king
+    TooManyKings(Color),
+    /// There is a pawn on the first or last rank
+    PawnOnBackRank(Pos),
+    /// The side that just moved is still in check
+    OpponentInCheck,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SanError {
+    /// The move isn't written in Standard Algebraic Notation
+    InvalidSyntax,
+    /// No legal move matches the notation
+    IllegalMove,
+    /// More than one legal move matches the notation
+    AmbiguousMove,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PgnError {
+    /// A `{` comment is never closed
+    UnterminatedComment,
+    /// A `(` variation is never closed
+    UnterminatedVariation,
+    /// A tag isn't a name followed by a quoted value inside `[]`
+    InvalidTag,
+    /// The `FEN` tag isn't a valid position
+    Fen(FenError),
+    /// The move at the given index couldn't be played
+    San { index: usize, error: SanError },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EpdError {
+    /// The first four fields aren't a valid FEN position
+    Fen(FenError),
+    /// An operation isn't ended by a `;`
+    UnterminatedOperation,
+    /// A string operand is missing its closing `"`
+    UnterminatedString,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum UciError {
+    /// The move isn't a from square, a to square, and an optional promotion piece
+    InvalidSyntax,
+    /// No legal move matches the notation
+    IllegalMove,
 }
 
 #[derive(Debug)]
 pub enum Error {
     InvalidDiff(InvalidDiff),
-    OutOfBounds,
-    NoPiece,
+    Uci(UciError),
+    OutOfBounds(OutOfBounds),
+    /// There was no piece on the given square
+    NoPiece(Pos),
+    /// Tried to undo a move when no moves have been applied
+    NoHistory,
+    /// The move isn't legal for the side to move
+    IllegalMove,
+    /// Tried to play a move after the game already ended
+    GameOver,
+    /// The move at the given index of a move list couldn't be parsed
+    San {
+        index: usize,
+        error: SanError,
+    },
 }
 
 impl From<OutOfBounds> for Error {
-    fn from(_: OutOfBounds) -> Self {
-        Error::OutOfBounds
+    fn from(e: OutOfBounds) -> Self {
+        Error::OutOfBounds(e)
     }
 }
 
@@ -31,3 +147,250 @@ impl From<InvalidDiff> for Error {
         Error::InvalidDiff(d)
     }
 }
+
+impl From<UciError> for Error {
+    fn from(e: UciError) -> Self {
+        Error::Uci(e)
+    }
+}
+
+mod fmt {
+    use super::*;
+    use core::fmt;
+
+    impl fmt::Display for OutOfBounds {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(
+                f,
+                "position ({}, {}) is out of the bounds of the board",
+                self.x, self.y
+            )
+        }
+    }
+
+    impl fmt::Display for ParsePosError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(
+                f,
+                "square must be a file `a`-`h` followed by a rank `1`-`8`"
+            )
+        }
+    }
+
+    impl fmt::Display for InvalidDiff {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let msg = match self {
+                InvalidDiff::CaptureOnMoveTy => "tried to move onto an occupied square",
+                InvalidDiff::MoveOnCaptureTy => "tried to capture on an empty square",
+                InvalidDiff::InvalidPromotionPiece => "tried to promote a piece that isn't a pawn",
+                InvalidDiff::InvalidPromotionRow => "tried to promote a pawn from the wrong row",
+                InvalidDiff::InvalidPromotionTarget => "tried to promote to a king or a pawn",
+                InvalidDiff::InvalidCastlePieces => {
+                    "tried to castle without a king and rook of the same color"
+                }
+                InvalidDiff::InvalidEnPassantPiece => {
+                    "tried to capture en passant without two pawns of opposite colors"
+                }
+            };
+
+            write!(f, "{}", msg)
+        }
+    }
+
+    impl fmt::Display for FenError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                FenError::MissingField => write!(f, "FEN is missing a field"),
+                FenError::TooManyFields => write!(f, "FEN has more than six fields"),
+                FenError::InvalidRankCount(count) => {
+                    write!(f, "FEN has {} ranks instead of eight", count)
+                }
+                FenError::InvalidRankLength(rank) => {
+                    write!(f, "rank {} doesn't describe exactly eight squares", rank)
+                }
+                FenError::UnknownPiece(c) => write!(f, "unknown piece `{}`", c),
+                FenError::InvalidColor => write!(f, "active color must be `w` or `b`"),
+                FenError::InvalidCastling(c) => write!(f, "unknown castling right `{}`", c),
+                FenError::InvalidEnPassant => write!(f, "invalid en passant target"),
+                FenError::InvalidCounter => write!(f, "invalid move counter"),
+            }
+        }
+    }
+
+    impl fmt::Display for ParseError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                ParseError::InvalidRankCount(count) => {
+                    write!(f, "diagram has {} ranks instead of eight", count)
+                }
+                ParseError::InvalidRankLength(rank) => {
+                    write!(f, "rank {} isn't exactly eight squares wide", rank)
+                }
+                ParseError::UnknownPiece(c) => write!(f, "unknown piece `{}`", c),
+            }
+        }
+    }
+
+    impl fmt::Display for PositionError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                PositionError::MissingKing(color) => write!(f, "{:?} has no king", color),
+                PositionError::TooManyKings(color) => {
+                    write!(f, "{:?} has more than one king", color)
+                }
+                PositionError::PawnOnBackRank(pos) => {
+                    write!(f, "pawn on {} can't be on the first or last rank", pos)
+                }
+                PositionError::OpponentInCheck => {
+                    write!(f, "the side not to move is in check")
+                }
+            }
+        }
+    }
+
+    impl fmt::Display for SanError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let msg = match self {
+                SanError::InvalidSyntax => "move isn't valid Standard Algebraic Notation",
+                SanError::IllegalMove => "no legal move matches the notation",
+                SanError::AmbiguousMove => "more than one legal move matches the notation",
+            };
+
+            write!(f, "{}", msg)
+        }
+    }
+
+    impl fmt::Display for PgnError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                PgnError::UnterminatedComment => write!(f, "comment is missing a closing `}}`"),
+                PgnError::UnterminatedVariation => {
+                    write!(f, "variation is missing a closing `)`")
+                }
+                PgnError::InvalidTag => write!(f, "tag must be a name and a quoted value"),
+                PgnError::Fen(e) => write!(f, "invalid FEN tag: {}", e),
+                PgnError::San { index, error } => {
+                    write!(f, "invalid move at index {}: {}", index, error)
+                }
+            }
+        }
+    }
+
+    impl fmt::Display for EpdError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                EpdError::Fen(e) => write!(f, "invalid position: {}", e),
+                EpdError::UnterminatedOperation => write!(f, "operation is missing a closing `;`"),
+                EpdError::UnterminatedString => write!(f, "string is missing a closing `\"`"),
+            }
+        }
+    }
+
+    impl fmt::Display for UciError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let msg = match self {
+                UciError::InvalidSyntax => "move isn't valid UCI notation",
+                UciError::IllegalMove => "no legal move matches the notation",
+            };
+
+            write!(f, "{}", msg)
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Error::InvalidDiff(d) => write!(f, "invalid diff: {}", d),
+                Error::Uci(e) => write!(f, "invalid UCI move: {}", e),
+                Error::OutOfBounds(e) => write!(f, "{}", e),
+                Error::NoPiece(pos) => write!(f, "no piece at {}", pos),
+                Error::NoHistory => write!(f, "no moves to undo"),
+                Error::IllegalMove => write!(f, "illegal move"),
+                Error::GameOver => write!(f, "the game is already over"),
+                Error::San { index, error } => {
+                    write!(f, "invalid SAN move at index {}: {}", index, error)
+                }
+            }
+        }
+    }
+
+    impl core::error::Error for OutOfBounds {}
+    impl core::error::Error for ParsePosError {}
+    impl core::error::Error for InvalidDiff {}
+    impl core::error::Error for FenError {}
+    impl core::error::Error for ParseError {}
+    impl core::error::Error for PositionError {}
+    impl core::error::Error for SanError {}
+    impl core::error::Error for UciError {}
+
+    impl core::error::Error for EpdError {
+        fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+            match self {
+                EpdError::Fen(e) => Some(e),
+                _ => None,
+            }
+        }
+    }
+
+    impl core::error::Error for PgnError {
+        fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+            match self {
+                PgnError::Fen(e) => Some(e),
+                PgnError::San { error, .. } => Some(error),
+                _ => None,
+            }
+        }
+    }
+
+    impl core::error::Error for Error {
+        fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+            match self {
+                Error::InvalidDiff(d) => Some(d),
+                Error::Uci(e) => Some(e),
+                Error::OutOfBounds(e) => Some(e),
+                Error::San { error, .. } => Some(error),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::board::Pos;
+    use crate::error::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn display() {
+        let pos = Pos::new_unchecked(4, 1);
+        assert_eq!(Error::NoPiece(pos).to_string(), "no piece at e2");
+        assert_eq!(
+            Error::InvalidDiff(InvalidDiff::CaptureOnMoveTy).to_string(),
+            "invalid diff: tried to move onto an occupied square"
+        );
+        assert_eq!(
+            FenError::InvalidRankLength(3).to_string(),
+            "rank 3 doesn't describe exactly eight squares"
+        );
+    }
+
+    #[test]
+    fn source() {
+        let err = Error::InvalidDiff(InvalidDiff::InvalidPromotionRow);
+        let source = err.source().unwrap();
+
+        assert_eq!(
+            source.to_string(),
+            InvalidDiff::InvalidPromotionRow.to_string()
+        );
+        assert!(Error::NoPiece(Pos::new_unchecked(0, 0)).source().is_none());
+
+        let err: Box<dyn std::error::Error> =
+            Box::new(Error::OutOfBounds(OutOfBounds { x: 8, y: -1 }));
+        assert_eq!(
+            err.to_string(),
+            "position (8, -1) is out of the bounds of the board"
+        );
+    }
+}
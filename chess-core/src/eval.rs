@@ -0,0 +1,287 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::board::{Board, GameCondition, Pos};
+use crate::pieces::{Color, Piece};
+
+/// The score `Board::evaluate` gives a checkmate, far larger than any material difference
+pub const MATE_SCORE: i32 = 100_000;
+
+/**
+ * a small bonus for pawns and knights that control the center,
+ * and a penalty for knights stuck on the rim
+ */
+fn placement_bonus(piece: Piece, Pos(x, y): Pos) -> i32 {
+    let center = |v: usize| v == 3 || v == 4;
+    let inner = |v: usize| (2..=5).contains(&v);
+    let rim = |v: usize| v == 0 || v == 7;
+
+    match piece {
+        Piece::Pawn | Piece::Knight if center(x) && center(y) => 20,
+        Piece::Knight if inner(x) && inner(y) => 10,
+        Piece::Knight if rim(x) || rim(y) => -10,
+        _ => 0,
+    }
+}
+
+impl Board {
+    /**
+     * A static evaluation of the position in centipawns from white's point of view,
+     * positive scores are good for white and negative scores are good for black
+     *
+     * This is the difference in material (using `Piece::value`, where a pawn is 100),
+     * plus a bonus of 20 for each pawn or knight on d4, e4, d5 or e5, a bonus of 10
+     * for each knight elsewhere in c3 to f6, and a penalty of 10 for each knight on the
+     * edge of the board. The bonuses are symmetric, so mirrored positions score 0
+     *
+     * If the side to move is checkmated this is `-MATE_SCORE` when white is mated
+     * and `MATE_SCORE` when black is mated, and a stalemate is always 0
+     */
+    pub fn evaluate(&self) -> i32 {
+        match self.game_condition(self.turn) {
+            GameCondition::Mate => {
+                return match self.turn {
+                    Color::White => -MATE_SCORE,
+                    Color::Black => MATE_SCORE,
+                }
+            }
+            GameCondition::Stale => return 0,
+            GameCondition::Safe | GameCondition::Check => (),
+        }
+
+        self.board
+            .iter()
+            .map(|(pos, piece, color)| {
+                let score = piece.value() as i32 * 100 + placement_bonus(piece, pos);
+
+                match color {
+                    Color::White => score,
+                    Color::Black => -score,
+                }
+            })
+            .sum()
+    }
+
+    /**
+     * Static exchange evaluation, the material the side to move wins in centipawns
+     * by capturing the piece on `target`, if both sides keep recapturing there with
+     * their least valuable attacker and either side may stop whenever it is behind
+     *
+     * Pieces lined up behind an attacker join in once it has captured, but pins are
+     * ignored, so a pinned piece may be counted as an attacker. This is 0 if the target
+     * is empty, holds a piece of the side to move, or can't be captured at all
+     */
+    pub fn see(&self, target: Pos) -> i32 {
+        let value = |piece: Piece| piece.value() as i32 * 100;
+
+        let victim = match self.board.get(target) {
+            Ok((piece, color)) if color != self.turn => piece,
+            _ => return 0,
+        };
+
+        // the king goes last, and only if the square is no longer defended
+        let least_valuable = |board: &Board, side: Color| {
+            let (pos, piece) = board
+                .attackers_of(target, side)
+                .into_iter()
+                .map(|pos| (pos, board.board.get(pos).unwrap().0))
+                .min_by_key(|&(_, piece)| match piece {
+                    Piece::King => u32::MAX,
+                    piece => piece.value(),
+                })?;
+
+            if piece == Piece::King && !board.attackers_of(target, side.opponent()).is_empty() {
+                None
+            } else {
+                Some((pos, piece))
+            }
+        };
+
+        let mut board = self.scratch();
+        let mut side = self.turn;
+
+        // gains[i] is the material won by whoever makes capture i, if it is the last
+        let mut gains = vec![value(victim)];
+
+        while let Some((pos, piece)) = least_valuable(&board, side) {
+            gains.push(value(piece) - gains[gains.len() - 1]);
+
+            board.board.clear(pos);
+            board.board.place(target, piece, side);
+            side = side.opponent();
+        }
+
+        let captures = gains.len() - 1;
+        if captures == 0 {
+            return 0;
+        }
+
+        // the last entry assumes a recapture that can't happen, so it is skipped
+        for i in (1..captures).rev() {
+            gains[i - 1] = -(-gains[i - 1]).max(gains[i]);
+        }
+
+        gains[0]
+    }
+
+    /**
+     * The squares of `color`'s pieces that are attacked by more enemy pieces than
+     * they are defended by, as counted by `attackers_of`
+     *
+     * This only counts attackers, so a well defended queen attacked by a pawn isn't
+     * hanging, use `see` to find out if a capture actually wins material. The king
+     * is never included
+     */
+    pub fn hanging_pieces(&self, color: Color) -> Vec<Pos> {
+        self.board
+            .iter()
+            .filter(|&(_, piece, c)| c == color && piece != Piece::King)
+            .map(|(pos, _, _)| pos)
+            .filter(|&pos| {
+                let attackers = self.attackers_of(pos, color.opponent()).len();
+                attackers > 0 && attackers > self.attackers_of(pos, color).len()
+            })
+            .collect()
+    }
+
+    /**
+     * How safe `color`'s king is, where higher is safer and 0 is neutral
+     *
+     * This is 10 for each friendly pawn on the king's file or the files next to it,
+     * one or two ranks in front of the king, minus 20 for each enemy attack (counted
+     * with `attackers_of`) on the king's square or the squares around it. This is
+     * 0 if there is no king of that color
+     */
+    pub fn king_safety(&self, color: Color) -> i32 {
+        let Pos(kx, ky) = match self.find_king(color) {
+            Some(pos) => pos,
+            None => return 0,
+        };
+
+        let ahead = |y: usize| match color {
+            Color::White => y > ky && y <= ky + 2,
+            Color::Black => y < ky && y + 2 >= ky,
+        };
+
+        let shield = self
+            .board
+            .iter()
+            .filter(|&(Pos(x, y), piece, c)| {
+                c == color && piece == Piece::Pawn && x.abs_diff(kx) <= 1 && ahead(y)
+            })
+            .count() as i32;
+
+        let attacks = (kx.saturating_sub(1)..=(kx + 1).min(7))
+            .flat_map(|x| (ky.saturating_sub(1)..=(ky + 1).min(7)).map(move |y| Pos(x, y)))
+            .map(|pos| self.attackers_of(pos, color.opponent()).len())
+            .sum::<usize>() as i32;
+
+        shield * 10 - attacks * 20
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn evaluate_start() {
+        assert_eq!(Board::new().evaluate(), 0);
+    }
+
+    #[test]
+    fn evaluate_material() {
+        let board =
+            Board::from_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        assert_eq!(board.evaluate(), 900);
+
+        let board =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+        assert_eq!(board.evaluate(), 20);
+
+        let board =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1").unwrap();
+        assert_eq!(board.evaluate(), 20);
+    }
+
+    #[test]
+    fn see() {
+        // the knight on d5 is defended by the pawn on e6, and the rook gets the pawn back
+        let board = Board::from_fen("4k3/8/4p3/3n4/4P3/8/8/3RK3 w - - 0 1").unwrap();
+        let d5 = "d5".parse().unwrap();
+
+        assert_eq!(board.see(d5), 300);
+
+        let board = Board::from_fen("4k3/8/4p3/3n4/8/8/8/3RK3 w - - 0 1").unwrap();
+        assert_eq!(board.see(d5), -200);
+
+        let board = Board::from_fen("4k3/8/8/3n4/8/8/8/3RK3 w - - 0 1").unwrap();
+        assert_eq!(board.see(d5), 300);
+
+        // the second rook joins in once the first has captured
+        let board = Board::from_fen("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+        assert_eq!(board.see(d5), 100);
+
+        // the king can only recapture if nothing else defends the square
+        let board = Board::from_fen("8/8/8/4k3/3p4/4P3/8/3RK3 w - - 0 1").unwrap();
+        assert_eq!(board.see("d4".parse().unwrap()), 100);
+
+        let board = Board::from_fen("8/8/8/4k3/3p4/8/8/3RK3 w - - 0 1").unwrap();
+        assert_eq!(board.see("d4".parse().unwrap()), -400);
+
+        assert_eq!(Board::new().see("e4".parse().unwrap()), 0);
+        assert_eq!(Board::new().see("e2".parse().unwrap()), 0);
+    }
+
+    #[test]
+    fn hanging_pieces() {
+        let b5 = "b5".parse().unwrap();
+
+        let board = Board::from_fen("4k3/8/8/1b6/8/8/8/1R2K3 w - - 0 1").unwrap();
+        assert_eq!(board.hanging_pieces(Color::Black), [b5]);
+        assert!(board.hanging_pieces(Color::White).is_empty());
+
+        // the pawn on a6 defends the bishop
+        let board = Board::from_fen("4k3/8/p7/1b6/8/8/8/1R2K3 w - - 0 1").unwrap();
+        assert!(board.hanging_pieces(Color::Black).is_empty());
+
+        // but not against two attackers
+        let board = Board::from_fen("4k3/8/p7/1b6/8/2N5/8/1R2K3 w - - 0 1").unwrap();
+        assert_eq!(board.hanging_pieces(Color::Black), [b5]);
+
+        assert!(Board::new().hanging_pieces(Color::White).is_empty());
+    }
+
+    #[test]
+    fn king_safety() {
+        let shielded = Board::from_fen("4k3/8/8/8/8/8/5PPP/6K1 w - - 0 1").unwrap();
+        let bare = Board::from_fen("4k3/8/8/8/8/8/8/6K1 w - - 0 1").unwrap();
+
+        assert_eq!(shielded.king_safety(Color::White), 30);
+        assert_eq!(bare.king_safety(Color::White), 0);
+        assert!(shielded.king_safety(Color::White) > bare.king_safety(Color::White));
+
+        // the rook attacks f1 and g1, but the king blocks it from h1
+        let attacked = Board::from_fen("4k3/8/8/8/8/8/8/r5K1 w - - 0 1").unwrap();
+        assert_eq!(attacked.king_safety(Color::White), -40);
+
+        let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(board.king_safety(Color::Black), 30);
+        assert_eq!(
+            Board::new().king_safety(Color::White),
+            Board::new().king_safety(Color::Black)
+        );
+    }
+
+    #[test]
+    fn evaluate_mate() {
+        let board = Board::from_fen("6k1/8/8/8/8/8/5PPP/r5K1 w - - 0 1").unwrap();
+        assert_eq!(board.evaluate(), -MATE_SCORE);
+
+        let board = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert_eq!(board.evaluate(), MATE_SCORE);
+
+        let board = Board::from_fen("k7/8/1Q6/8/8/8/8/7K b - - 0 1").unwrap();
+        assert_eq!(board.evaluate(), 0);
+    }
+}
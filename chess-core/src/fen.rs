@@ -0,0 +1,370 @@
+use crate::board::{Board, CastlingRights, Pos, RawBoard};
+use crate::error::FenError;
+use crate::pieces::{Color, Piece};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// The standard starting position in Forsyth-Edwards Notation
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+impl Board {
+    /**
+     * Parses a position from Forsyth-Edwards Notation
+     *
+     * The halfmove clock and fullmove number may be left off,
+     * in which case they default to 0 and 1
+     *
+     * Every field is honoured by the moves played afterwards, so the board
+     * continues exactly as if the game had been played up to this position,
+     * including an en passant capture on the very next move
+     */
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let mut fields = fen.split_whitespace();
+        let mut field = || fields.next().ok_or(FenError::MissingField);
+
+        let board = parse_placement(field()?)?;
+        let turn = parse_color(field()?)?;
+        let castling = parse_castling(field()?)?;
+        let en_passant = parse_en_passant(field()?)?;
+
+        let halfmove_clock = fields.next().map_or(Ok(0), parse_counter)?;
+        let fullmove_number = fields.next().map_or(Ok(1), parse_counter)?;
+
+        if fields.next().is_some() {
+            return Err(FenError::TooManyFields);
+        }
+
+        let mut board = Board::with_turn(board, turn);
+        board.castling = castling;
+        board.en_passant = en_passant;
+        board.halfmove_clock = halfmove_clock;
+        board.fullmove_number = fullmove_number;
+
+        Ok(board)
+    }
+
+    /**
+     * Writes the position in Forsyth-Edwards Notation
+     */
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+
+        for y in (0..8).rev() {
+            let mut empty = 0;
+
+            for x in 0..8 {
+                match self.get(Pos(x, y)) {
+                    Ok((piece, color)) => {
+                        if empty != 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+
+                        let ident = piece.get_ident();
+                        fen.push(match color {
+                            Color::White => ident,
+                            Color::Black => ident.to_ascii_lowercase(),
+                        });
+                    }
+                    Err(_) => empty += 1,
+                }
+            }
+
+            if empty != 0 {
+                fen.push_str(&empty.to_string());
+            }
+
+            if y != 0 {
+                fen.push('/');
+            }
+        }
+
+        fen.push(' ');
+        fen.push(match self.turn {
+            Color::White => 'w',
+            Color::Black => 'b',
+        });
+
+        fen.push(' ');
+        let castling = [
+            (CastlingRights::WHITE_KINGSIDE, 'K'),
+            (CastlingRights::WHITE_QUEENSIDE, 'Q'),
+            (CastlingRights::BLACK_KINGSIDE, 'k'),
+            (CastlingRights::BLACK_QUEENSIDE, 'q'),
+        ];
+        let len = fen.len();
+        for &(rights, c) in &castling {
+            if self.castling.contains(rights) {
+                fen.push(c);
+            }
+        }
+        if fen.len() == len {
+            fen.push('-');
+        }
+
+        fen.push(' ');
+        match self.en_passant {
+            Some(pos) => fen.push_str(&pos.to_string()),
+            None => fen.push('-'),
+        }
+
+        fen.push_str(&format!(
+            " {} {}",
+            self.halfmove_clock, self.fullmove_number
+        ));
+
+        fen
+    }
+}
+
+fn parse_placement(placement: &str) -> Result<RawBoard, FenError> {
+    let ranks = placement.split('/').collect::<Vec<_>>();
+
+    if ranks.len() != 8 {
+        return Err(FenError::InvalidRankCount(ranks.len()));
+    }
+
+    let mut board = RawBoard::default();
+
+    for (rank, y) in ranks.into_iter().zip((0..8).rev()) {
+        let mut x = 0;
+
+        for c in rank.chars() {
+            if let Some(empty) = c.to_digit(10).filter(|d| (1..=8).contains(d)) {
+                x += empty as usize;
+                continue;
+            }
+
+            let piece =
+                Piece::from_ident(c.to_ascii_uppercase()).ok_or(FenError::UnknownPiece(c))?;
+            let color = if c.is_ascii_uppercase() {
+                Color::White
+            } else {
+                Color::Black
+            };
+
+            if x >= 8 {
+                return Err(FenError::InvalidRankLength(y + 1));
+            }
+
+            board.set(Pos(x, y), piece, color);
+            x += 1;
+        }
+
+        if x != 8 {
+            return Err(FenError::InvalidRankLength(y + 1));
+        }
+    }
+
+    Ok(board)
+}
+
+fn parse_color(color: &str) -> Result<Color, FenError> {
+    match color {
+        "w" => Ok(Color::White),
+        "b" => Ok(Color::Black),
+        _ => Err(FenError::InvalidColor),
+    }
+}
+
+fn parse_castling(castling: &str) -> Result<CastlingRights, FenError> {
+    let mut rights = CastlingRights::none();
+
+    if castling == "-" {
+        return Ok(rights);
+    }
+
+    for c in castling.chars() {
+        rights.insert(match c {
+            'K' => CastlingRights::WHITE_KINGSIDE,
+            'Q' => CastlingRights::WHITE_QUEENSIDE,
+            'k' => CastlingRights::BLACK_KINGSIDE,
+            'q' => CastlingRights::BLACK_QUEENSIDE,
+            _ => return Err(FenError::InvalidCastling(c)),
+        });
+    }
+
+    Ok(rights)
+}
+
+fn parse_en_passant(en_passant: &str) -> Result<Option<Pos>, FenError> {
+    if en_passant == "-" {
+        return Ok(None);
+    }
+
+    match en_passant.parse() {
+        Ok(pos @ Pos(_, 2)) | Ok(pos @ Pos(_, 5)) => Ok(Some(pos)),
+        _ => Err(FenError::InvalidEnPassant),
+    }
+}
+
+fn parse_counter(counter: &str) -> Result<u32, FenError> {
+    counter.parse().map_err(|_| FenError::InvalidCounter)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn from_fen_start() {
+        let board = Board::from_fen(START_FEN).unwrap();
+
+        assert!(board == Board::new());
+    }
+
+    #[test]
+    fn to_fen_start() {
+        assert_eq!(Board::new().to_fen(), START_FEN);
+    }
+
+    #[test]
+    fn to_fen_round_trip() {
+        let fens = [
+            "4k3/8/8/8/3pP3/8/8/4K2R b K e3 0 42",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 12 30",
+        ];
+
+        for &fen in &fens {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn to_fen_after_move() {
+        let mut board = Board::new();
+
+        board
+            .apply(Diff {
+                ty: DiffType::Move,
+                from: Pos::new_unchecked(4, 1),
+                to: Pos::new_unchecked(4, 3),
+            })
+            .unwrap();
+
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+
+        board
+            .apply(Diff {
+                ty: DiffType::Move,
+                from: Pos::new_unchecked(6, 7),
+                to: Pos::new_unchecked(5, 5),
+            })
+            .unwrap();
+
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2"
+        );
+    }
+
+    #[test]
+    fn from_fen_fields() {
+        let board = Board::from_fen("4k3/8/8/8/3pP3/8/8/4K2R b K e3 0 42").unwrap();
+
+        assert_eq!(board.to_move(), Color::Black);
+        assert_eq!(board.castling_rights(), CastlingRights::WHITE_KINGSIDE);
+        assert_eq!(board.en_passant(), Some(Pos::new_unchecked(4, 2)));
+        assert_eq!(board.halfmove_clock(), 0);
+        assert_eq!(board.fullmove_number(), 42);
+        assert_eq!(
+            board.get(Pos::new_unchecked(3, 3)).ok(),
+            Some((Piece::Pawn, Color::Black))
+        );
+        assert_eq!(
+            board.get(Pos::new_unchecked(7, 0)).ok(),
+            Some((Piece::Rook, Color::White))
+        );
+    }
+
+    #[test]
+    fn from_fen_mid_game() {
+        let fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+        let mut board = Board::from_fen(fen).unwrap();
+
+        let moves = board
+            .get_possible_moves(Pos::E5)
+            .unwrap()
+            .collect::<Vec<_>>();
+        let en_passant = Diff {
+            ty: DiffType::EnPassant { captured: Pos::F5 },
+            from: Pos::E5,
+            to: Pos::F6,
+        };
+
+        assert!(moves.contains(&en_passant));
+        assert!(moves.iter().all(|diff| diff.to != Pos::D6));
+
+        board.make_move(en_passant).unwrap();
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/ppp1p1pp/5P2/3p4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3"
+        );
+
+        board.make_move(board.parse_san("Nc6").unwrap()).unwrap();
+        assert_eq!(
+            board.to_fen(),
+            "r1bqkbnr/ppp1p1pp/2n2P2/3p4/8/8/PPPP1PPP/RNBQKBNR w KQkq - 1 4"
+        );
+
+        // without the en passant target the capture isn't allowed
+        let board = Board::from_fen(&fen.replace("f6", "-")).unwrap();
+        assert!(board
+            .get_possible_moves(Pos::E5)
+            .unwrap()
+            .all(|diff| diff.to != Pos::F6));
+    }
+
+    #[test]
+    fn from_fen_errors() {
+        let err = |fen| Board::from_fen(fen).err();
+
+        assert_eq!(err(""), Some(FenError::MissingField));
+        assert_eq!(
+            err("8/8/8/8/8/8/8 w - -"),
+            Some(FenError::InvalidRankCount(7))
+        );
+        assert_eq!(
+            err("8/8/8/8/8/8/8/7 w - -"),
+            Some(FenError::InvalidRankLength(1))
+        );
+        assert_eq!(
+            err("9/8/8/8/8/8/8/8 w - -"),
+            Some(FenError::UnknownPiece('9'))
+        );
+        assert_eq!(
+            err("8/8/8/8/8/8/8/ppppppppp w - -"),
+            Some(FenError::InvalidRankLength(1))
+        );
+        assert_eq!(
+            err("8/8/8/8/8/8/8/7x w - -"),
+            Some(FenError::UnknownPiece('x'))
+        );
+        assert_eq!(err("8/8/8/8/8/8/8/8 x - -"), Some(FenError::InvalidColor));
+        assert_eq!(
+            err("8/8/8/8/8/8/8/8 w KX -"),
+            Some(FenError::InvalidCastling('X'))
+        );
+        assert_eq!(
+            err("8/8/8/8/8/8/8/8 w - e4"),
+            Some(FenError::InvalidEnPassant)
+        );
+        assert_eq!(
+            err("8/8/8/8/8/8/8/8 w - - x 1"),
+            Some(FenError::InvalidCounter)
+        );
+        assert_eq!(
+            err("8/8/8/8/8/8/8/8 w - - 0 -1"),
+            Some(FenError::InvalidCounter)
+        );
+        assert_eq!(
+            err("8/8/8/8/8/8/8/8 w - - 0 1 x"),
+            Some(FenError::TooManyFields)
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use crate::board::{Board, Diff, GameResult};
+use crate::error::Error;
+use crate::pieces::Color;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/**
+ * A game in progress, the position it started from, every move played since,
+ * and the result so far, which is kept up to date as moves are pushed and popped
+ *
+ * A game can also end without a move by `resign` or `agree_draw`
+ */
+#[derive(Debug, Clone)]
+pub struct Game {
+    start: Board,
+    board: Board,
+    moves: Vec<Diff>,
+    result: GameResult,
+}
+
+impl Game {
+    /**
+     * a game from the standard starting position
+     */
+    pub fn new() -> Self {
+        Self::from_position(Board::new())
+    }
+
+    pub fn from_position(start: Board) -> Self {
+        Self {
+            result: start.result(),
+            board: start.clone(),
+            start,
+            moves: Vec::new(),
+        }
+    }
+
+    /**
+     * the current position
+     */
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    pub fn moves(&self) -> &[Diff] {
+        &self.moves
+    }
+
+    /**
+     * The result of the game as of the last move, including draws by
+     * the fifty-move rule and threefold repetition
+     */
+    pub fn result(&self) -> GameResult {
+        self.result
+    }
+
+    /**
+     * Plays a move for the side to move
+     *
+     * Fails with `Error::GameOver` if the game already has a result,
+     * and with `Error::IllegalMove` if the move isn't legal
+     */
+    pub fn push(&mut self, diff: Diff) -> Result<(), Error> {
+        if self.result != GameResult::Ongoing {
+            return Err(Error::GameOver);
+        }
+
+        self.board.make_move(diff)?;
+        self.moves.push(diff);
+        self.result = self.board.result();
+        Ok(())
+    }
+
+    /**
+     * Ends the game with a win for the other side, if it isn't already over
+     */
+    pub fn resign(&mut self, color: Color) {
+        if self.result == GameResult::Ongoing {
+            self.result = match color {
+                Color::White => GameResult::BlackWins,
+                Color::Black => GameResult::WhiteWins,
+            };
+        }
+    }
+
+    /**
+     * Ends the game in a draw, if it isn't already over
+     */
+    pub fn agree_draw(&mut self) {
+        if self.result == GameResult::Ongoing {
+            self.result = GameResult::Draw;
+        }
+    }
+
+    /**
+     * Takes back the last move, or returns `None` if no moves have been played
+     *
+     * The result is worked out again from the position, so this also
+     * takes back a resignation or an agreed draw
+     */
+    pub fn pop(&mut self) -> Option<Diff> {
+        let diff = self.moves.pop()?;
+        self.board
+            .undo()
+            .expect("every pushed move is in the board's history");
+        self.result = self.board.result();
+        Some(diff)
+    }
+
+    /**
+     * writes the game like `to_pgn`, with the game's result
+     */
+    pub fn to_pgn(&self) -> String {
+        crate::pgn::write_pgn(&self.moves, &self.start, Some(self.result))
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    fn push_san(game: &mut Game, san: &str) -> Result<(), Error> {
+        let diff = game.board().parse_san(san).unwrap();
+        game.push(diff)
+    }
+
+    #[test]
+    fn scholars_mate() {
+        let mut game = Game::new();
+
+        for &san in &["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6"] {
+            push_san(&mut game, san).unwrap();
+            assert_eq!(game.result(), GameResult::Ongoing);
+        }
+
+        push_san(&mut game, "Qxf7#").unwrap();
+        assert_eq!(game.result(), GameResult::WhiteWins);
+        assert_eq!(game.moves().len(), 7);
+
+        let any = game.board().all_legal_moves(Color::Black).next();
+        assert!(any.is_none());
+
+        let last = game.pop().unwrap();
+        assert_eq!(game.result(), GameResult::Ongoing);
+        game.push(last).unwrap();
+
+        let diff = game.moves()[0];
+        assert!(matches!(game.push(diff), Err(Error::GameOver)));
+        assert!(game.to_pgn().ends_with("4. Qxf7# 1-0\n"));
+    }
+
+    #[test]
+    fn repetition() {
+        let mut game = Game::new();
+
+        for _ in 0..2 {
+            for &san in &["Nf3", "Nf6", "Ng1", "Ng8"] {
+                assert_eq!(game.result(), GameResult::Ongoing);
+                push_san(&mut game, san).unwrap();
+            }
+        }
+
+        assert_eq!(game.result(), GameResult::Draw);
+
+        game.pop();
+        assert_eq!(game.result(), GameResult::Ongoing);
+        assert!(game.board() != game.start());
+    }
+
+    #[test]
+    fn resign() {
+        let mut game = Game::new();
+        push_san(&mut game, "e4").unwrap();
+
+        game.resign(Color::White);
+        assert_eq!(game.result(), GameResult::BlackWins);
+        assert!(matches!(push_san(&mut game, "e5"), Err(Error::GameOver)));
+        assert!(game.to_pgn().ends_with("1. e4 0-1\n"));
+
+        // the game is already over, so this doesn't change the result
+        game.agree_draw();
+        assert_eq!(game.result(), GameResult::BlackWins);
+
+        game.pop();
+        assert_eq!(game.result(), GameResult::Ongoing);
+
+        game.agree_draw();
+        assert_eq!(game.result(), GameResult::Draw);
+        assert!(matches!(push_san(&mut game, "e4"), Err(Error::GameOver)));
+    }
+
+    #[test]
+    fn pop_empty() {
+        let mut game = Game::new();
+
+        assert!(game.pop().is_none());
+        assert!(matches!(
+            game.push(Diff {
+                ty: DiffType::Move,
+                from: Pos::E2,
+                to: Pos::E5,
+            }),
+            Err(Error::IllegalMove)
+        ));
+    }
+}
@@ -1,10 +1,52 @@
+// without the `std` feature only `alloc` is needed, which can be checked with a
+// bare metal target, i.e. `cargo build --no-default-features --target thumbv7em-none-eabihf`
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "proptest")]
+mod arbitrary;
+mod ascii;
+mod bitboard;
 mod board;
+mod builder;
+mod chess960;
+mod epd;
 mod error;
+mod eval;
+mod fen;
+mod game;
 mod math;
+mod notation;
+mod pgn;
 mod pieces;
+#[cfg(feature = "rand")]
+mod random;
+#[cfg(feature = "search")]
+mod search;
+mod tables;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod zobrist;
 
 #[cfg(test)]
 mod test;
 
-pub use board::Board;
+pub use bitboard::Bitboard;
+pub use board::{
+    Board, CastlingRights, Diff, DiffType, GameCondition, GameResult, MoveLegality, NullUndo,
+    PieceIter, Pos, RawBoard, Undo,
+};
+pub use builder::BoardBuilder;
+pub use epd::{from_epd, EpdOps};
+pub use error::*;
+pub use eval::MATE_SCORE;
+pub use fen::START_FEN;
+pub use game::Game;
+pub use math::Vector;
+pub use pgn::{from_pgn, to_pgn};
 pub use pieces::*;
+#[cfg(feature = "search")]
+pub use search::best_move;
+#[cfg(feature = "wasm")]
+pub use wasm::{new_game, WasmGame};
@@ -1,4 +1,4 @@
-use std::ops::{Add, Div, Mul, Sub};
+use core::ops::{Add, Div, Mul, Sub};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Vector {
@@ -10,6 +10,31 @@ impl Vector {
     pub fn zero() -> Self {
         Vector { x: 0, y: 0 }
     }
+
+    pub fn dot(self, other: Vector) -> i32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /**
+     * rotates the vector by +90 degrees, i.e. from the a file towards the 8th rank
+     */
+    pub fn perp(self) -> Vector {
+        Vector {
+            x: -self.y,
+            y: self.x,
+        }
+    }
+
+    /**
+     * reduces the vector to a single step in the same direction,
+     * by taking the sign of each component
+     */
+    pub fn step(self) -> Vector {
+        Vector {
+            x: self.x.signum(),
+            y: self.y.signum(),
+        }
+    }
 }
 
 impl Default for Vector {
@@ -61,3 +86,37 @@ macro_rules! impl_op {
 
 impl_op! { i32 Mul mul *= }
 impl_op! { i32 Div div /= }
+
+#[cfg(test)]
+mod test {
+    use crate::math::*;
+
+    #[test]
+    fn dot() {
+        let a = Vector { x: 2, y: 3 };
+        let b = Vector { x: -1, y: 4 };
+
+        assert_eq!(a.dot(b), 10);
+        assert_eq!(a.dot(Vector::zero()), 0);
+        assert_eq!(Vector { x: 1, y: 0 }.dot(Vector { x: 0, y: 1 }), 0);
+    }
+
+    #[test]
+    fn perp() {
+        let a = Vector { x: 2, y: 3 };
+
+        assert_eq!(a.perp(), Vector { x: -3, y: 2 });
+        assert_eq!(a.perp().dot(a), 0);
+        assert_eq!(a.perp().perp(), a * -1);
+        assert_eq!(a.perp().perp().perp().perp(), a);
+    }
+
+    #[test]
+    fn step() {
+        assert_eq!(Vector { x: 3, y: 3 }.step(), Vector { x: 1, y: 1 });
+        assert_eq!(Vector { x: -5, y: 2 }.step(), Vector { x: -1, y: 1 });
+        assert_eq!(Vector { x: 0, y: -7 }.step(), Vector { x: 0, y: -1 });
+        assert_eq!(Vector { x: 4, y: 0 }.step(), Vector { x: 1, y: 0 });
+        assert_eq!(Vector::zero().step(), Vector::zero());
+    }
+}
@@ -0,0 +1,543 @@
+use crate::board::{Board, Diff, DiffType, Pos};
+use crate::error::{Error, SanError, UciError};
+use crate::pieces::Piece;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+impl Board {
+    /**
+     * Writes the diff in Standard Algebraic Notation, i.e. `Nc3`, `exd5`, `e8=Q+`, or `O-O`
+     *
+     * The diff should be a legal move in the current position, like
+     * the ones from `get_possible_moves`
+     *
+     * Panics if there is no piece on the diff's source square
+     */
+    pub fn to_san(&self, diff: Diff) -> String {
+        let Diff { ty, from, to } = diff;
+
+        let mut san = match ty {
+            DiffType::Castle { rook_from, .. } if rook_from.0 > from.0 => "O-O".to_string(),
+            DiffType::Castle { .. } => "O-O-O".to_string(),
+            _ => {
+                let (piece, color) = self.get(from).expect("no piece on the source square");
+                let capture = diff.is_capture();
+
+                let mut san = String::new();
+
+                if piece == Piece::Pawn {
+                    if capture {
+                        san.push(from.file_char());
+                    }
+                } else {
+                    san.push(piece.get_ident());
+
+                    // other pieces of the same kind that could also move to the target square
+                    let others = self
+                        .board
+                        .iter()
+                        .filter(|&(pos, pt, c)| pos != from && pt == piece && c == color)
+                        .filter(|&(pos, _, _)| {
+                            self.get_possible_moves(pos)
+                                .unwrap()
+                                .any(|diff| diff.to == to)
+                        })
+                        .map(|(pos, _, _)| pos)
+                        .collect::<Vec<_>>();
+
+                    if !others.is_empty() {
+                        if others.iter().all(|pos| pos.0 != from.0) {
+                            san.push(from.file_char());
+                        } else if others.iter().all(|pos| pos.1 != from.1) {
+                            san.push_str(&(from.1 + 1).to_string());
+                        } else {
+                            san.push_str(&from.to_string());
+                        }
+                    }
+                }
+
+                if capture {
+                    san.push('x');
+                }
+
+                san.push_str(&to.to_string());
+
+                if let DiffType::Promote { piece, .. } = ty {
+                    san.push('=');
+                    san.push(piece.get_ident());
+                }
+
+                san
+            }
+        };
+
+        if self.gives_check(diff) {
+            let mut after = self.scratch();
+            after.apply_with_undo(diff).unwrap();
+
+            if after.legal_move_count(after.to_move()) == 0 {
+                san.push('#');
+            } else {
+                san.push('+');
+            }
+        }
+
+        san
+    }
+
+    /**
+     * Parses a move in Standard Algebraic Notation, and finds the
+     * legal move for the side to move that it describes
+     */
+    pub fn parse_san(&self, san: &str) -> Result<Diff, SanError> {
+        let san = san.trim_end_matches(|c| "+#!?".contains(c));
+
+        let castle = match san {
+            "O-O" | "0-0" => Some(true),
+            "O-O-O" | "0-0-0" => Some(false),
+            _ => None,
+        };
+
+        let candidates = self.all_legal_moves(self.turn);
+
+        if let Some(kingside) = castle {
+            return unique(candidates.filter(|diff| match diff.ty {
+                DiffType::Castle { rook_from, .. } => (rook_from.0 > diff.from.0) == kingside,
+                _ => false,
+            }));
+        }
+
+        let mut chars = san.chars();
+
+        let piece = match san.chars().next() {
+            Some(c) if c.is_ascii_uppercase() => {
+                chars.next();
+                Piece::from_ident(c).ok_or(SanError::InvalidSyntax)?
+            }
+            _ => Piece::Pawn,
+        };
+
+        let rest = chars.as_str();
+        let (rest, promotion) = match rest.find('=') {
+            Some(i) => {
+                let mut promotion = rest[i + 1..].chars();
+                match (
+                    promotion.next().and_then(Piece::from_ident),
+                    promotion.next(),
+                ) {
+                    (Some(promotion), None) => (&rest[..i], Some(promotion)),
+                    _ => return Err(SanError::InvalidSyntax),
+                }
+            }
+            None => (rest, None),
+        };
+
+        if rest.len() < 2 || !rest.is_char_boundary(rest.len() - 2) {
+            return Err(SanError::InvalidSyntax);
+        }
+
+        let (rest, to) = rest.split_at(rest.len() - 2);
+        let to = to.parse::<Pos>().map_err(|_| SanError::InvalidSyntax)?;
+
+        let (rest, capture) = match rest.strip_suffix('x') {
+            Some(rest) => (rest, true),
+            None => (rest, false),
+        };
+
+        let mut file = None;
+        let mut rank = None;
+        for c in rest.chars() {
+            match c {
+                'a'..='h' if file.is_none() && rank.is_none() => {
+                    file = Some(c as usize - 'a' as usize)
+                }
+                '1'..='8' if rank.is_none() => rank = Some(c as usize - '1' as usize),
+                _ => return Err(SanError::InvalidSyntax),
+            }
+        }
+
+        unique(candidates.filter(|diff| {
+            let (pt, _) = self.get(diff.from).unwrap();
+
+            let (is_capture, promotes_to) = match diff.ty {
+                DiffType::Capture { .. } | DiffType::EnPassant { .. } => (true, None),
+                DiffType::Promote { piece, capture } => (capture, Some(piece)),
+                DiffType::Castle { .. } => return false,
+                DiffType::Move => (false, None),
+            };
+
+            pt == piece
+                && diff.to == to
+                && promotes_to == promotion
+                && (is_capture || !capture)
+                && file.is_none_or(|x| diff.from.0 == x)
+                && rank.is_none_or(|y| diff.from.1 == y)
+        }))
+    }
+
+    /**
+     * Plays each move in Standard Algebraic Notation in order, stopping at
+     * the first one that can't be parsed or played
+     *
+     * Moves before the failing one stay applied, and the error holds the index
+     * of the move that failed
+     */
+    pub fn apply_san_sequence(&mut self, moves: &[&str]) -> Result<(), Error> {
+        for (index, san) in moves.iter().enumerate() {
+            let diff = self
+                .parse_san(san)
+                .map_err(|error| Error::San { index, error })?;
+            self.make_move(diff)?;
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Writes the diff in the long algebraic notation used by UCI, i.e. `e2e4` or `e7e8q`
+     */
+    pub fn to_uci(&self, diff: Diff) -> String {
+        let mut uci = format!("{}{}", diff.from, diff.to);
+
+        if let DiffType::Promote { piece, .. } = diff.ty {
+            uci.push(piece.get_ident().to_ascii_lowercase());
+        }
+
+        uci
+    }
+
+    /**
+     * Parses a move in the long algebraic notation used by UCI, and finds
+     * the legal move for the side to move that it describes
+     */
+    pub fn parse_uci(&self, uci: &str) -> Result<Diff, Error> {
+        if !uci.is_ascii() || (uci.len() != 4 && uci.len() != 5) {
+            Err(UciError::InvalidSyntax)?;
+        }
+
+        let from = uci[0..2]
+            .parse::<Pos>()
+            .map_err(|_| UciError::InvalidSyntax)?;
+        let to = uci[2..4]
+            .parse::<Pos>()
+            .map_err(|_| UciError::InvalidSyntax)?;
+
+        let promotion = match uci[4..].chars().next() {
+            Some(c) if "qrbn".contains(c) => Piece::from_ident(c.to_ascii_uppercase()),
+            Some(_) => Err(UciError::InvalidSyntax)?,
+            None => None,
+        };
+
+        match self.get(from) {
+            Ok((_, color)) if color == self.turn => (),
+            _ => Err(UciError::IllegalMove)?,
+        }
+
+        let diff = self.get_possible_moves(from).unwrap().find(|diff| {
+            let promotes_to = match diff.ty {
+                DiffType::Promote { piece, .. } => Some(piece),
+                _ => None,
+            };
+
+            diff.to == to && promotes_to == promotion
+        });
+
+        Ok(diff.ok_or(UciError::IllegalMove)?)
+    }
+
+    /**
+     * Parses a move in coordinate notation, which is UCI with an optional piece
+     * letter in front, a `-` or `x` between the squares, and an `=` before the
+     * promotion, i.e. `e2-e4`, `Nb1-c3`, `e4xd5`, or `e7-e8=Q`, and finds the legal
+     * move for the side to move that it describes
+     *
+     * If a piece letter is given it must match the moving piece, but the separator
+     * isn't checked, so `e2xe4` is the same as `e2-e4`. Errors are the same as `parse_uci`
+     */
+    pub fn parse_coordinate(&self, s: &str) -> Result<Diff, Error> {
+        let (piece, rest) = match s.chars().next() {
+            Some(c) if c.is_ascii_uppercase() => {
+                let piece = Piece::from_ident(c).ok_or(UciError::InvalidSyntax)?;
+                (Some(piece), &s[1..])
+            }
+            _ => (None, s),
+        };
+
+        if !rest.is_ascii() || rest.len() < 4 {
+            Err(UciError::InvalidSyntax)?;
+        }
+
+        let (from, rest) = rest.split_at(2);
+        let rest = rest.strip_prefix(|c| c == '-' || c == 'x').unwrap_or(rest);
+        let to = rest.get(..2).ok_or(UciError::InvalidSyntax)?;
+        let promotion = &rest[2..];
+        let promotion = promotion.strip_prefix('=').unwrap_or(promotion);
+
+        let uci = format!("{}{}{}", from, to, promotion.to_ascii_lowercase());
+        let diff = self.parse_uci(&uci)?;
+
+        match piece {
+            Some(piece) if self.get(diff.from)?.0 != piece => Err(UciError::IllegalMove)?,
+            _ => Ok(diff),
+        }
+    }
+}
+
+/**
+ * gets the only diff in the iterator
+ */
+fn unique(mut diffs: impl Iterator<Item = Diff>) -> Result<Diff, SanError> {
+    match (diffs.next(), diffs.next()) {
+        (Some(diff), None) => Ok(diff),
+        (Some(_), Some(_)) => Err(SanError::AmbiguousMove),
+        (None, _) => Err(SanError::IllegalMove),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    fn san(fen: &str, from: &str, to: &str) -> String {
+        let board = Board::from_fen(fen).unwrap();
+        let from = from.parse().unwrap();
+        let to = to.parse().unwrap();
+
+        let diff = board
+            .get_possible_moves(from)
+            .unwrap()
+            .find(|diff| diff.to == to)
+            .unwrap();
+
+        board.to_san(diff)
+    }
+
+    #[test]
+    fn to_san_pieces() {
+        assert_eq!(san(START_FEN, "b1", "c3"), "Nc3");
+        assert_eq!(san(START_FEN, "e2", "e4"), "e4");
+        assert_eq!(san(START_FEN, "g1", "f3"), "Nf3");
+    }
+
+    #[test]
+    fn to_san_captures() {
+        let fen = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2";
+        assert_eq!(san(fen, "e4", "d5"), "exd5");
+
+        let fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+        assert_eq!(san(fen, "e5", "f6"), "exf6");
+
+        let fen = "4k3/8/8/3p4/8/2N5/8/4K3 w - - 0 1";
+        assert_eq!(san(fen, "c3", "d5"), "Nxd5");
+    }
+
+    #[test]
+    fn to_san_disambiguation() {
+        let fen = "6k1/8/8/8/8/8/8/R4RK1 w - - 0 1";
+        assert_eq!(san(fen, "a1", "d1"), "Rad1");
+        assert_eq!(san(fen, "f1", "d1"), "Rfd1");
+
+        let fen = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(san(fen, "a1", "a3"), "R1a3");
+
+        let fen = "4k3/8/8/8/8/2N1N3/8/4K3 w - - 0 1";
+        assert_eq!(san(fen, "c3", "d5"), "Ncd5");
+
+        let fen = "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1";
+        assert_eq!(san(fen, "a1", "b2"), "Qa1b2");
+    }
+
+    #[test]
+    fn to_san_special() {
+        let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+        assert_eq!(san(fen, "e1", "g1"), "O-O");
+        assert_eq!(san(fen, "e1", "c1"), "O-O-O");
+
+        let fen = "8/4P3/8/8/8/8/8/k3K3 w - - 0 1";
+        assert_eq!(san(fen, "e7", "e8"), "e8=Q");
+
+        let fen = "k7/4P3/8/8/8/8/8/4K3 w - - 0 1";
+        assert_eq!(san(fen, "e7", "e8"), "e8=Q+");
+    }
+
+    #[test]
+    fn parse_san() {
+        let board = Board::new();
+        let diff = board.parse_san("Nf3").unwrap();
+
+        assert_eq!(diff.from, "g1".parse().unwrap());
+        assert_eq!(diff.to, "f3".parse().unwrap());
+        assert_eq!(diff.ty, DiffType::Move);
+
+        let diff = board.parse_san("e4").unwrap();
+        assert_eq!(diff.from, "e2".parse().unwrap());
+        assert_eq!(diff.to, "e4".parse().unwrap());
+
+        assert_eq!(board.parse_san("Nf4"), Err(SanError::IllegalMove));
+        assert_eq!(board.parse_san("e5"), Err(SanError::IllegalMove));
+        assert_eq!(board.parse_san("O-O"), Err(SanError::IllegalMove));
+        assert_eq!(board.parse_san("Zf3"), Err(SanError::InvalidSyntax));
+        assert_eq!(board.parse_san("N"), Err(SanError::InvalidSyntax));
+        assert_eq!(board.parse_san(""), Err(SanError::InvalidSyntax));
+    }
+
+    #[test]
+    fn parse_san_round_trip() {
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+            "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+            "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        ];
+
+        for &fen in &fens {
+            let board = Board::from_fen(fen).unwrap();
+
+            for diff in board.all_legal_moves(board.to_move()) {
+                assert_eq!(board.parse_san(&board.to_san(diff)), Ok(diff));
+            }
+        }
+    }
+
+    #[test]
+    fn parse_san_ambiguous() {
+        let board = Board::from_fen("6k1/8/8/8/8/8/8/R4RK1 w - - 0 1").unwrap();
+
+        assert_eq!(board.parse_san("Rd1"), Err(SanError::AmbiguousMove));
+        assert_eq!(
+            board.parse_san("Rad1").map(|diff| diff.from),
+            Ok("a1".parse().unwrap())
+        );
+
+        let board = Board::from_fen("8/4P3/8/8/8/8/8/k3K3 w - - 0 1").unwrap();
+
+        assert_eq!(board.parse_san("e8"), Err(SanError::IllegalMove));
+        assert_eq!(
+            board.parse_san("e8=N").map(|diff| diff.ty),
+            Ok(DiffType::Promote {
+                piece: Piece::Knight,
+                capture: false
+            })
+        );
+    }
+
+    #[test]
+    fn apply_san_sequence() {
+        let mut board = Board::new();
+        board.apply_san_sequence(&["e4", "e5", "Nf3"]).unwrap();
+
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+        );
+
+        let mut board = Board::new();
+        let err = board.apply_san_sequence(&["e4", "e5", "e5"]).unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::San {
+                index: 2,
+                error: SanError::IllegalMove
+            }
+        ));
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+        );
+    }
+
+    #[test]
+    fn uci_round_trip() {
+        let board = Board::from_fen("4k3/1P6/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+
+        let diff = board.parse_uci("b7b8q").unwrap();
+        assert_eq!(
+            diff.ty,
+            DiffType::Promote {
+                piece: Piece::Queen,
+                capture: false
+            }
+        );
+        assert_eq!(board.to_uci(diff), "b7b8q");
+
+        let diff = board.parse_uci("e1c1").unwrap();
+        assert_eq!(
+            diff.ty,
+            DiffType::Castle {
+                rook_from: "a1".parse().unwrap(),
+                rook_to: "d1".parse().unwrap()
+            }
+        );
+        assert_eq!(board.to_uci(diff), "e1c1");
+
+        let diff = Board::new().parse_uci("e2e4").unwrap();
+        assert_eq!(Board::new().to_uci(diff), "e2e4");
+    }
+
+    #[test]
+    fn parse_uci_errors() {
+        let board = Board::from_fen("4k3/1P6/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+        let err = |uci| match board.parse_uci(uci) {
+            Err(Error::Uci(e)) => e,
+            res => panic!("{:?}", res),
+        };
+
+        assert_eq!(err("b7b8"), UciError::IllegalMove);
+        assert_eq!(err("e8d8"), UciError::IllegalMove);
+        assert_eq!(err("a1a1"), UciError::IllegalMove);
+        assert_eq!(err("b7b8k"), UciError::InvalidSyntax);
+        assert_eq!(err("b7b9"), UciError::InvalidSyntax);
+        assert_eq!(err("b7"), UciError::InvalidSyntax);
+        assert_eq!(err("b7b8qq"), UciError::InvalidSyntax);
+    }
+
+    #[test]
+    fn parse_coordinate() {
+        let board = Board::new();
+        let e4 = board.parse_uci("e2e4").unwrap();
+
+        assert_eq!(board.parse_coordinate("e2-e4").unwrap(), e4);
+        assert_eq!(board.parse_coordinate("e2e4").unwrap(), e4);
+        assert_eq!(board.parse_coordinate("Pe2-e4").unwrap(), e4);
+        assert_eq!(
+            board.parse_coordinate("Nb1-c3").unwrap(),
+            board.parse_san("Nc3").unwrap()
+        );
+
+        let board = Board::from_fen("4k3/1P6/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            board.parse_coordinate("e4xd5").unwrap(),
+            board.parse_san("exd5").unwrap()
+        );
+        assert_eq!(
+            board.parse_coordinate("b7-b8=N").unwrap(),
+            board.parse_uci("b7b8n").unwrap()
+        );
+
+        let err = |s| match board.parse_coordinate(s) {
+            Err(Error::Uci(e)) => e,
+            res => panic!("{:?}", res),
+        };
+
+        assert_eq!(err("Ne4-e5"), UciError::IllegalMove);
+        assert_eq!(err("e4-e6"), UciError::IllegalMove);
+        assert_eq!(err("b7-b8"), UciError::IllegalMove);
+        assert_eq!(err("Xe4-e5"), UciError::InvalidSyntax);
+        assert_eq!(err("e4-"), UciError::InvalidSyntax);
+        assert_eq!(err("e4--e5"), UciError::InvalidSyntax);
+        assert_eq!(err("b7-b8=K"), UciError::InvalidSyntax);
+    }
+
+    #[test]
+    fn to_san_check_and_mate() {
+        let fen = "6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(san(fen, "a1", "a8"), "Ra8#");
+        assert_eq!(san(fen, "a1", "a7"), "Ra7");
+
+        let fen = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(san(fen, "a1", "a8"), "Ra8+");
+    }
+}
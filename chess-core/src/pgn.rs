@@ -0,0 +1,369 @@
+use crate::board::{Board, Diff, GameResult};
+use crate::error::{PgnError, SanError};
+use crate::fen::START_FEN;
+use crate::pieces::Color;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// The tokens that end the movetext of a game
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+enum Token<'a> {
+    /// A `[Name "value"]` pair from the header
+    Tag(&'a str, &'a str),
+    /// A move, move number, or result from the movetext
+    Word(&'a str),
+}
+
+/**
+ * Splits a PGN into tags and words, dropping comments, variations, and NAGs
+ */
+fn tokenize(pgn: &str) -> Result<Vec<Token<'_>>, PgnError> {
+    let mut tokens = Vec::new();
+    let mut rest = pgn;
+
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '{' => {
+                let end = rest.find('}').ok_or(PgnError::UnterminatedComment)?;
+                rest = &rest[end + 1..];
+            }
+            ';' => {
+                let end = rest.find('\n').unwrap_or(rest.len());
+                rest = &rest[end..];
+            }
+            '(' => {
+                let mut depth = 0;
+                let end = rest
+                    .char_indices()
+                    .find(|&(_, c)| {
+                        match c {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => (),
+                        }
+
+                        depth == 0
+                    })
+                    .ok_or(PgnError::UnterminatedVariation)?
+                    .0;
+                rest = &rest[end + 1..];
+            }
+            '[' => {
+                let end = rest.find(']').ok_or(PgnError::InvalidTag)?;
+                let tag = rest[1..end].trim();
+                let (name, value) = tag
+                    .find(char::is_whitespace)
+                    .map(|split| (&tag[..split], tag[split..].trim()))
+                    .ok_or(PgnError::InvalidTag)?;
+
+                if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+                    return Err(PgnError::InvalidTag);
+                }
+
+                tokens.push(Token::Tag(name, &value[1..value.len() - 1]));
+                rest = &rest[end + 1..];
+            }
+            c if c.is_whitespace() => rest = &rest[c.len_utf8()..],
+            _ => {
+                let end = rest
+                    .find(|c: char| c.is_whitespace() || "{;([".contains(c))
+                    .unwrap_or(rest.len());
+                let word = &rest[..end];
+
+                if !word.starts_with('$') {
+                    tokens.push(Token::Word(word));
+                }
+
+                rest = &rest[end..];
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/**
+ * the token for a result in the movetext and the `Result` tag
+ */
+pub(crate) fn result_token(result: GameResult) -> &'static str {
+    match result {
+        GameResult::WhiteWins => "1-0",
+        GameResult::BlackWins => "0-1",
+        GameResult::Draw => "1/2-1/2",
+        GameResult::Ongoing => "*",
+    }
+}
+
+/**
+ * Strips a move number like `12.` or `12...` off the front of a word,
+ * which may leave it empty
+ */
+fn strip_move_number(word: &str) -> &str {
+    let rest = word.trim_start_matches(|c: char| c.is_ascii_digit());
+
+    if rest.len() < word.len() && rest.starts_with('.') {
+        rest.trim_start_matches('.')
+    } else {
+        word
+    }
+}
+
+/**
+ * Loads a game in Portable Game Notation, and plays it out
+ *
+ * The game starts from the `FEN` tag if there is one, and from the standard
+ * starting position otherwise. All other tags, comments in `{}` or after `;`,
+ * variations in `()`, and NAGs like `$1` are ignored, and the game stops at
+ * the first result token (`1-0`, `0-1`, `1/2-1/2` or `*`)
+ *
+ * Returns the final position and every move that was played
+ */
+pub fn from_pgn(pgn: &str) -> Result<(Board, Vec<Diff>), PgnError> {
+    let mut board = Board::new();
+    let mut moves = Vec::new();
+
+    for token in tokenize(pgn)? {
+        let word = match token {
+            Token::Tag("FEN", fen) if moves.is_empty() => {
+                board = Board::from_fen(fen).map_err(PgnError::Fen)?;
+                continue;
+            }
+            Token::Tag(..) => continue,
+            Token::Word(word) => word,
+        };
+
+        if RESULTS.contains(&word) {
+            break;
+        }
+
+        let san = strip_move_number(word);
+
+        if san.is_empty() {
+            continue;
+        }
+
+        let index = moves.len();
+        let diff = board
+            .parse_san(san)
+            .map_err(|error| PgnError::San { index, error })?;
+
+        board.make_move(diff).map_err(|_| PgnError::San {
+            index,
+            error: SanError::IllegalMove,
+        })?;
+        moves.push(diff);
+    }
+
+    Ok((board, moves))
+}
+
+/**
+ * Writes a game in Portable Game Notation, with the moves played from `start`
+ *
+ * The seven tag roster is filled with the placeholders PGN uses for unknown
+ * values, along with `SetUp` and `FEN` tags if `start` isn't the standard
+ * starting position. The result tag and token come from `Board::result` on
+ * the final position, and the movetext is wrapped to fit in 80 columns
+ *
+ * # Panics
+ *
+ * If any of the moves isn't legal in the position it is played from
+ */
+pub fn to_pgn(moves: &[Diff], start: &Board) -> String {
+    write_pgn(moves, start, None)
+}
+
+/**
+ * `to_pgn`, but with the given result instead of the final position's if there is one,
+ * for games that end by resignation or agreement
+ */
+pub(crate) fn write_pgn(moves: &[Diff], start: &Board, result: Option<GameResult>) -> String {
+    let mut board = start.clone();
+    let mut words = Vec::new();
+
+    for (i, &diff) in moves.iter().enumerate() {
+        match board.to_move() {
+            Color::White => words.push(format!("{}.", board.fullmove_number())),
+            Color::Black if i == 0 => words.push(format!("{}...", board.fullmove_number())),
+            Color::Black => (),
+        }
+
+        words.push(board.to_san(diff));
+        board
+            .make_move(diff)
+            .expect("every move in a PGN must be legal");
+    }
+
+    let result = result_token(result.unwrap_or_else(|| board.result()));
+    words.push(result.to_string());
+
+    let mut pgn = String::new();
+    let tags = [
+        ("Event", "?"),
+        ("Site", "?"),
+        ("Date", "????.??.??"),
+        ("Round", "?"),
+        ("White", "?"),
+        ("Black", "?"),
+        ("Result", result),
+    ];
+
+    for (name, value) in tags.iter() {
+        pgn.push_str(&format!("[{} \"{}\"]\n", name, value));
+    }
+
+    let fen = start.to_fen();
+    if fen != START_FEN {
+        pgn.push_str(&format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", fen));
+    }
+
+    pgn.push('\n');
+
+    let mut line = String::new();
+    for word in words {
+        if !line.is_empty() && line.len() + 1 + word.len() > 80 {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+
+    pgn.push_str(&line);
+    pgn.push('\n');
+    pgn
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    /// The first game of the 1972 World Championship match, Spassky vs Fischer, cut short
+    const GAME: &str = r#"[Event "World Championship"]
+[Site "Reykjavik ISL"]
+[Date "1972.07.11"]
+[Round "1"]
+[White "Spassky, Boris V"]
+[Black "Fischer, Robert James"]
+[Result "1-0"]
+
+1. d4 Nf6 2. c4 e6 3. Nf3 d5 4. Nc3 Bb4 5. e3 O-O 6. Bd3 c5 7. O-O Nc6
+8. a3 Ba5 {a rare retreat} 9. Ne2 dxc4 10. Bxc4 Bb6 (10... cxd4 11. exd4) 11. dxc5 Qxd1
+12. Rxd1 Bxc5 13. b4 $1 Be7 14. Bb2 Bd7 1-0
+"#;
+
+    #[test]
+    fn from_pgn_game() {
+        let (board, moves) = from_pgn(GAME).unwrap();
+
+        assert_eq!(moves.len(), 28);
+        assert_eq!(
+            board.to_fen(),
+            "r4rk1/pp1bbppp/2n1pn2/8/1PB5/P3PN2/1B2NPPP/R2R2K1 w - - 3 15"
+        );
+
+        let mut replay = Board::new();
+        for &diff in &moves {
+            replay.make_move(diff).unwrap();
+        }
+        assert!(replay == board);
+    }
+
+    #[test]
+    fn from_pgn_stops_at_result() {
+        let (board, moves) = from_pgn("1.e4 e5 2.Nf3 * 2...Nc6").unwrap();
+
+        assert_eq!(moves.len(), 3);
+        assert_eq!(board.to_move(), Color::Black);
+    }
+
+    #[test]
+    fn from_pgn_fen_tag() {
+        let pgn = r#"[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"]
+
+1. e4 Kd7 *"#;
+        let (board, _) = from_pgn(pgn).unwrap();
+
+        assert_eq!(board.to_fen(), "8/3k4/8/8/4P3/8/8/4K3 w - - 1 2");
+    }
+
+    /**
+     * the movetext of a PGN, without the tags
+     */
+    fn movetext(pgn: &str) -> String {
+        pgn.lines()
+            .filter(|line| !line.starts_with('['))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .trim()
+            .to_string()
+    }
+
+    #[test]
+    fn to_pgn_round_trip() {
+        let (_, moves) = from_pgn(GAME).unwrap();
+        let pgn = to_pgn(&moves, &Board::new());
+
+        assert!(pgn.lines().all(|line| line.len() <= 80));
+        assert!(pgn.starts_with("[Event \"?\"]\n[Site \"?\"]\n"));
+        assert!(pgn.contains("[Result \"*\"]\n\n1. d4 Nf6"));
+        assert_eq!(
+            movetext(&pgn),
+            "1. d4 Nf6 2. c4 e6 3. Nf3 d5 4. Nc3 Bb4 5. e3 O-O 6. Bd3 c5 7. O-O Nc6 \
+             8. a3 Ba5 9. Ne2 dxc4 10. Bxc4 Bb6 11. dxc5 Qxd1 12. Rxd1 Bxc5 13. b4 Be7 \
+             14. Bb2 Bd7 *"
+        );
+
+        let (board, again) = from_pgn(&pgn).unwrap();
+        assert_eq!(again, moves);
+        assert_eq!(to_pgn(&again, &Board::new()), pgn);
+        assert_eq!(board.to_move(), Color::White);
+    }
+
+    #[test]
+    fn to_pgn_result() {
+        let (_, moves) = from_pgn("1. f3 e5 2. g4 Qh4#").unwrap();
+        let pgn = to_pgn(&moves, &Board::new());
+
+        assert!(pgn.contains("[Result \"0-1\"]"));
+        assert_eq!(movetext(&pgn), "1. f3 e5 2. g4 Qh4# 0-1");
+
+        let start = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 1").unwrap();
+        let (_, moves) =
+            from_pgn("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 1\"]\n1... Kd7 2. e4").unwrap();
+        let pgn = to_pgn(&moves, &start);
+
+        assert!(pgn.contains("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 1\"]"));
+        assert_eq!(movetext(&pgn), "1... Kd7 2. e4 *");
+    }
+
+    #[test]
+    fn from_pgn_errors() {
+        assert_eq!(
+            from_pgn("1. e4 e5 2. Ke3").unwrap_err(),
+            PgnError::San {
+                index: 2,
+                error: SanError::IllegalMove
+            }
+        );
+        assert_eq!(
+            from_pgn("1. e4 {never closed").unwrap_err(),
+            PgnError::UnterminatedComment
+        );
+        assert_eq!(
+            from_pgn("1. e4 (1. d4").unwrap_err(),
+            PgnError::UnterminatedVariation
+        );
+        assert_eq!(
+            from_pgn("[Event]\n1. e4").unwrap_err(),
+            PgnError::InvalidTag
+        );
+    }
+}
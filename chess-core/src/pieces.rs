@@ -1,6 +1,7 @@
 use crate::math::Vector;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Piece {
     King,
     Queen,
@@ -11,6 +12,7 @@ pub enum Piece {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     Black,
     White,
@@ -47,6 +49,53 @@ impl Piece {
         }
     }
 
+    /**
+     * the figurine glyph for the piece, i.e. `♔` for the white king
+     */
+    pub fn unicode(self, color: Color) -> char {
+        match (color, self) {
+            (Color::White, Piece::King) => '♔',
+            (Color::White, Piece::Queen) => '♕',
+            (Color::White, Piece::Rook) => '♖',
+            (Color::White, Piece::Bishop) => '♗',
+            (Color::White, Piece::Knight) => '♘',
+            (Color::White, Piece::Pawn) => '♙',
+            (Color::Black, Piece::King) => '♚',
+            (Color::Black, Piece::Queen) => '♛',
+            (Color::Black, Piece::Rook) => '♜',
+            (Color::Black, Piece::Bishop) => '♝',
+            (Color::Black, Piece::Knight) => '♞',
+            (Color::Black, Piece::Pawn) => '♟',
+        }
+    }
+
+    /**
+     * the classic point value of the piece, the king has no value
+     * since it can never be traded
+     */
+    pub fn value(self) -> u32 {
+        match self {
+            Piece::Pawn => 1,
+            Piece::Knight => 3,
+            Piece::Bishop => 3,
+            Piece::Rook => 5,
+            Piece::Queen => 9,
+            Piece::King => 0,
+        }
+    }
+
+    pub fn from_ident(ident: char) -> Option<Self> {
+        match ident {
+            'P' => Some(Piece::Pawn),
+            'N' => Some(Piece::Knight),
+            'B' => Some(Piece::Bishop),
+            'R' => Some(Piece::Rook),
+            'Q' => Some(Piece::Queen),
+            'K' => Some(Piece::King),
+            _ => None,
+        }
+    }
+
     pub fn get_moves(self) -> &'static [VMove] {
         macro_rules! moves {
             ($name: ident
@@ -75,8 +124,7 @@ impl Piece {
         moves! {
             PAWN_MOVES
             Pawn
-            ( 0, 1, 1, Move)
-            ( 0, 2, 1, Move)
+            ( 0, 1, 2, Move)
             ( 1, 1, 1, Capture)
             (-1, 1, 1, Capture)
         }
@@ -163,20 +211,78 @@ impl Color {
             Color::Black => -1,
         }
     }
+
+    pub fn opponent(self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
 }
 
 impl MoveType {
+    /**
+     * can this move capture, true for both `Capture` and `MoveCapture`
+     */
     pub fn is_capture(self) -> bool {
-        match self {
-            MoveType::Move => false,
-            _ => true,
-        }
+        !matches!(self, MoveType::Move)
     }
 
+    /**
+     * can this move go to an empty square, true for both `Move` and `MoveCapture`
+     */
     pub fn is_normal(self) -> bool {
-        match self {
-            MoveType::Capture => false,
-            _ => true,
-        }
+        !matches!(self, MoveType::Capture)
+    }
+
+    /**
+     * can this move only capture, like a pawn's diagonal step
+     */
+    pub fn is_capture_only(self) -> bool {
+        self == MoveType::Capture
+    }
+
+    /**
+     * can this move never capture, like a pawn's forward step
+     */
+    pub fn is_move_only(self) -> bool {
+        self == MoveType::Move
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pieces::*;
+
+    #[test]
+    fn opponent() {
+        assert_eq!(Color::White.opponent(), Color::Black);
+        assert_eq!(Color::Black.opponent(), Color::White);
+    }
+
+    #[test]
+    fn move_type() {
+        let predicates = |ty: MoveType| {
+            (
+                ty.is_capture(),
+                ty.is_normal(),
+                ty.is_capture_only(),
+                ty.is_move_only(),
+            )
+        };
+
+        assert_eq!(predicates(MoveType::Move), (false, true, false, true));
+        assert_eq!(predicates(MoveType::Capture), (true, false, true, false));
+        assert_eq!(
+            predicates(MoveType::MoveCapture),
+            (true, true, false, false)
+        );
+    }
+
+    #[test]
+    fn unicode() {
+        assert_eq!(Piece::Queen.unicode(Color::White), '♕');
+        assert_eq!(Piece::King.unicode(Color::White), '♔');
+        assert_eq!(Piece::King.unicode(Color::Black), '♚');
     }
 }
@@ -0,0 +1,56 @@
+use alloc::vec::Vec;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::board::{Board, Diff};
+
+impl Board {
+    /**
+     * A legal move for the side to move, with every legal move equally likely,
+     * or `None` if there aren't any
+     *
+     * The same position and a generator in the same state always give the same move
+     */
+    pub fn random_legal_move<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Diff> {
+        let moves = self.all_legal_moves(self.turn).collect::<Vec<_>>();
+        moves.choose(rng).copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    /**
+     * plays up to 40 random moves from the start, checking that each is legal
+     */
+    fn random_game(seed: u64) -> Vec<Diff> {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut board = Board::new();
+        let mut moves = Vec::new();
+
+        while let Some(diff) = board.random_legal_move(&mut rng) {
+            assert!(board.is_legal(diff));
+            board.make_move(diff).unwrap();
+            moves.push(diff);
+
+            if moves.len() == 40 {
+                break;
+            }
+        }
+
+        moves
+    }
+
+    #[test]
+    fn random_legal_move() {
+        assert_eq!(random_game(0x5eed), random_game(0x5eed));
+
+        let mated = Board::from_fen("6k1/8/8/8/8/8/5PPP/r5K1 w - - 0 1").unwrap();
+        let mut rng = SmallRng::seed_from_u64(0x5eed);
+        assert_eq!(mated.random_legal_move(&mut rng), None);
+    }
+}
@@ -0,0 +1,208 @@
+use alloc::vec::Vec;
+
+use crate::board::{Board, Diff, DiffType, GameCondition};
+use crate::eval::MATE_SCORE;
+use crate::pieces::{Color, Piece};
+
+/// larger than any score a position can get, even a mate
+const INFINITY: i32 = MATE_SCORE + 1;
+
+impl Board {
+    /**
+     * Sorts moves so the ones most likely to be good are searched first
+     *
+     * Captures come first, ordered by most valuable victim and then least valuable
+     * attacker (using `Piece::value`), then the moves that give check, and then
+     * everything else. Moves in the same group keep their order
+     */
+    pub fn order_moves(&self, moves: &mut [Diff]) {
+        let value = |pos| self.get(pos).map_or(0, |(piece, _)| piece.value());
+
+        moves.sort_by_cached_key(|&diff| {
+            let victim = match diff.ty {
+                DiffType::Capture { cap } => value(cap),
+                DiffType::EnPassant { .. } => Piece::Pawn.value(),
+                DiffType::Promote { capture: true, .. } => value(diff.to),
+                _ if self.gives_check(diff) => return (1, 0, 0),
+                _ => return (2, 0, 0),
+            };
+
+            (0, u32::MAX - victim, value(diff.from))
+        });
+    }
+}
+
+/**
+ * `Board::evaluate` from the point of view of the side to move, where mates
+ * found `ply` moves from the root score a little less the further away they are
+ */
+fn relative_eval(board: &Board, ply: i32) -> i32 {
+    let score = match board.to_move() {
+        Color::White => board.evaluate(),
+        Color::Black => -board.evaluate(),
+    };
+
+    // `evaluate` only scores a mate when the side to move is mated, so this is always negative
+    if score == -MATE_SCORE {
+        ply - MATE_SCORE
+    } else {
+        score
+    }
+}
+
+/**
+ * The score of the position for the side to move, searched `depth` moves deep
+ *
+ * The legal moves of every position on the current line are kept on the end of `moves`,
+ * so the whole search shares one buffer
+ */
+fn negamax(
+    board: &mut Board,
+    depth: u32,
+    ply: i32,
+    mut alpha: i32,
+    beta: i32,
+    moves: &mut Vec<Diff>,
+) -> i32 {
+    if depth == 0 {
+        return relative_eval(board, ply);
+    }
+
+    let start = moves.len();
+    moves.extend(board.all_legal_moves(board.to_move()));
+    let end = moves.len();
+
+    if start == end {
+        return match board.game_condition(board.to_move()) {
+            GameCondition::Mate => ply - MATE_SCORE,
+            _ => 0,
+        };
+    }
+
+    board.order_moves(&mut moves[start..end]);
+
+    let mut best = -INFINITY;
+
+    for i in start..end {
+        let undo = board
+            .apply_with_undo(moves[i])
+            .expect("generated moves are legal");
+        let score = -negamax(board, depth - 1, ply + 1, -beta, -alpha, moves);
+        board.unmake(undo);
+
+        best = best.max(score);
+        alpha = alpha.max(best);
+
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    moves.truncate(start);
+    best
+}
+
+/**
+ * Searches `depth` moves (plies) ahead with negamax and alpha-beta pruning, and
+ * returns the best move for the side to move with its score, or `None` if there
+ * are no legal moves. At least one ply is always searched
+ *
+ * The score is in centipawns from the point of view of the side to move, like
+ * `Board::evaluate` but negated when black is to move. A mate in `n` plies scores
+ * `MATE_SCORE - n` for the side giving it, and a stalemate scores 0. Draws by
+ * repetition and the fifty-move rule aren't detected
+ *
+ * The board is returned to the position it started in
+ */
+pub fn best_move(board: &mut Board, depth: u32) -> Option<(Diff, i32)> {
+    let mut moves = board.all_legal_moves(board.to_move()).collect::<Vec<_>>();
+    let root = moves.len();
+    board.order_moves(&mut moves);
+
+    let mut best = None;
+    let mut alpha = -INFINITY;
+
+    for i in 0..root {
+        let diff = moves[i];
+        let undo = board
+            .apply_with_undo(diff)
+            .expect("generated moves are legal");
+        let score = -negamax(board, depth.max(1) - 1, 1, -INFINITY, -alpha, &mut moves);
+        board.unmake(undo);
+
+        if score > alpha {
+            alpha = score;
+            best = Some((diff, score));
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod test {
+    use super::best_move;
+    use crate::*;
+
+    #[test]
+    fn mate_in_one() {
+        let mut board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let fen = board.to_fen();
+
+        let (diff, score) = best_move(&mut board, 3).unwrap();
+        assert_eq!(board.to_fen(), fen);
+        assert_eq!(score, MATE_SCORE - 1);
+
+        board.make_move(diff).unwrap();
+        assert!(board.is_checkmate());
+
+        // the same mate with colors swapped
+        let mut board = Board::from_fen("r5k1/8/8/8/8/8/5PPP/6K1 b - - 0 1").unwrap();
+        let (diff, score) = best_move(&mut board, 2).unwrap();
+        assert_eq!(score, MATE_SCORE - 1);
+
+        board.make_move(diff).unwrap();
+        assert!(board.is_checkmate());
+    }
+
+    #[test]
+    fn wins_material() {
+        // the queen on d5 is free, but taking the pawn on h7 loses the rook
+        let mut board = Board::from_fen("6k1/6pp/8/3q4/8/8/8/3RK2R w - - 0 1").unwrap();
+        let (diff, _) = best_move(&mut board, 2).unwrap();
+
+        assert_eq!(diff.to, Pos::D5);
+    }
+
+    #[test]
+    fn order_moves() {
+        let board = Board::from_fen("4k3/8/8/3q4/8/3Q4/P7/4K3 w - - 0 1").unwrap();
+
+        let push = board.parse_san("a3").unwrap();
+        let check = board.parse_san("Qe3+").unwrap();
+        let trade = board.parse_san("Qxd5").unwrap();
+
+        let mut moves = vec![push, check, trade];
+        board.order_moves(&mut moves);
+        assert_eq!(moves, [trade, check, push]);
+
+        // pawn takes queen comes before queen takes queen
+        let board = Board::from_fen("4k3/8/8/3q4/4P3/3Q4/8/4K3 w - - 0 1").unwrap();
+
+        let by_queen = board.parse_san("Qxd5").unwrap();
+        let by_pawn = board.parse_san("exd5").unwrap();
+
+        let mut moves = vec![by_queen, by_pawn];
+        board.order_moves(&mut moves);
+        assert_eq!(moves, [by_pawn, by_queen]);
+    }
+
+    #[test]
+    fn no_moves() {
+        let mut board = Board::from_fen("k7/8/1Q6/8/8/8/8/7K b - - 0 1").unwrap();
+        assert_eq!(best_move(&mut board, 3), None);
+
+        let mut board = Board::from_fen("6k1/8/8/8/8/8/5PPP/r5K1 w - - 0 1").unwrap();
+        assert_eq!(best_move(&mut board, 3), None);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::board::Pos;
+
+/**
+ * The squares a knight or king can jump to from each square,
+ * built once at compile time
+ */
+pub(crate) struct Table {
+    squares: [[Pos; 8]; 64],
+    len: [usize; 64],
+}
+
+/**
+ * the jumps a knight can make, in the same order as the knight's `VMove`s,
+ * so the generated moves keep their order
+ */
+pub(crate) const KNIGHT_DELTAS: [(i32, i32); 8] = [
+    (1, -2),
+    (1, 2),
+    (-1, -2),
+    (-1, 2),
+    (2, -1),
+    (2, 1),
+    (-2, -1),
+    (-2, 1),
+];
+
+/**
+ * the steps a king can make, in the same order as the king's `VMove`s
+ */
+pub(crate) const KING_DELTAS: [(i32, i32); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+pub(crate) static KNIGHT: Table = Table::new(&KNIGHT_DELTAS);
+pub(crate) static KING: Table = Table::new(&KING_DELTAS);
+
+impl Table {
+    const fn new(deltas: &[(i32, i32); 8]) -> Self {
+        let mut table = Table {
+            squares: [[Pos(0, 0); 8]; 64],
+            len: [0; 64],
+        };
+
+        let mut square = 0;
+        while square < 64 {
+            let x = (square % 8) as i32;
+            let y = (square / 8) as i32;
+
+            let mut i = 0;
+            while i < deltas.len() {
+                let (dx, dy) = deltas[i];
+                let (x, y) = (x + dx, y + dy);
+
+                if x >= 0 && x < 8 && y >= 0 && y < 8 {
+                    table.squares[square][table.len[square]] = Pos(x as usize, y as usize);
+                    table.len[square] += 1;
+                }
+
+                i += 1;
+            }
+
+            square += 1;
+        }
+
+        table
+    }
+
+    pub(crate) fn get(&self, Pos(x, y): Pos) -> &[Pos] {
+        let square = y * 8 + x;
+        &self.squares[square][..self.len[square]]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pieces::{Piece, VMove};
+
+    fn from_vmoves(piece: Piece, pos: Pos) -> Vec<Pos> {
+        piece
+            .get_moves()
+            .iter()
+            .flat_map(|&VMove(_, del, _, _)| Pos::try_from(pos.into() + del))
+            .collect()
+    }
+
+    #[test]
+    fn matches_vmoves() {
+        for pos in Pos::all() {
+            assert_eq!(KNIGHT.get(pos), &from_vmoves(Piece::Knight, pos)[..]);
+            assert_eq!(KING.get(pos), &from_vmoves(Piece::King, pos)[..]);
+        }
+
+        assert_eq!(KNIGHT.get(Pos(0, 0)).len(), 2);
+        assert_eq!(KING.get(Pos(4, 4)).len(), 8);
+
+        #[cfg(feature = "bitboard")]
+        for pos in Pos::all() {
+            use crate::bitboard::{bit, KING_ATTACKS, KNIGHT_ATTACKS};
+
+            let mask = |squares: &[Pos]| squares.iter().fold(0, |bits, &pos| bits | bit(pos));
+            let square = pos.1 * 8 + pos.0;
+
+            assert_eq!(KNIGHT_ATTACKS[square], mask(KNIGHT.get(pos)));
+            assert_eq!(KING_ATTACKS[square], mask(KING.get(pos)));
+        }
+    }
+}
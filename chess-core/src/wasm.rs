@@ -0,0 +1,139 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use wasm_bindgen::prelude::*;
+
+use crate::board::{Board, Pos};
+use crate::game::Game;
+
+/**
+ * A `Game` for JavaScript, where squares are written like `e4`
+ * and moves are written in UCI notation like `e2e4` or `e7e8q`
+ */
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct WasmGame {
+    game: Game,
+}
+
+/**
+ * a game from the standard starting position
+ */
+#[wasm_bindgen]
+pub fn new_game() -> WasmGame {
+    WasmGame::default()
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    /**
+     * Starts a game from a position in Forsyth-Edwards Notation,
+     * or returns `undefined` if it isn't valid
+     */
+    pub fn from_fen(fen: &str) -> Option<WasmGame> {
+        let board = Board::from_fen(fen).ok()?;
+
+        Some(Self {
+            game: Game::from_position(board),
+        })
+    }
+
+    /**
+     * The legal moves of the piece on the square, which is empty if the
+     * square is invalid, empty, or holds a piece of the side not to move
+     */
+    pub fn legal_moves(&self, square: &str) -> Vec<String> {
+        let board = self.game.board();
+
+        let pos = match square.parse::<Pos>() {
+            Ok(pos) => pos,
+            Err(_) => return Vec::new(),
+        };
+
+        match board.get(pos) {
+            Ok((_, color)) if color == board.to_move() => (),
+            _ => return Vec::new(),
+        }
+
+        board
+            .get_possible_moves(pos)
+            .map(|moves| moves.map(|diff| board.to_uci(diff)).collect())
+            .unwrap_or_default()
+    }
+
+    /**
+     * Plays the move if it is legal, and returns whether it was played
+     */
+    pub fn make_move(&mut self, uci: &str) -> bool {
+        match self.game.board().parse_uci(uci) {
+            Ok(diff) => self.game.push(diff).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    /**
+     * Takes back the last move, and returns whether there was one to take back
+     */
+    pub fn undo(&mut self) -> bool {
+        self.game.pop().is_some()
+    }
+
+    pub fn fen(&self) -> String {
+        self.game.board().to_fen()
+    }
+
+    pub fn pgn(&self) -> String {
+        self.game.to_pgn()
+    }
+
+    /**
+     * the result as written in PGN, `1-0`, `0-1`, `1/2-1/2`, or `*` if the game isn't over
+     */
+    pub fn result(&self) -> String {
+        crate::pgn::result_token(self.game.result()).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn play() {
+        let mut game = new_game();
+
+        let mut moves = game.legal_moves("e2");
+        moves.sort();
+        assert_eq!(moves, ["e2e3", "e2e4"]);
+        assert!(game.legal_moves("e7").is_empty());
+        assert!(game.legal_moves("e4").is_empty());
+        assert!(game.legal_moves("z9").is_empty());
+
+        assert!(game.make_move("e2e4"));
+        assert!(!game.make_move("e2e4"));
+        assert!(!game.make_move("nonsense"));
+        assert_eq!(
+            game.fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+
+        for &uci in &["e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"] {
+            assert!(game.make_move(uci));
+        }
+        assert_eq!(game.result(), "1-0");
+
+        assert!(game.undo());
+        assert_eq!(game.result(), "*");
+    }
+
+    #[test]
+    fn from_fen() {
+        let game = WasmGame::from_fen("8/4P3/8/8/8/8/8/k3K3 w - - 0 1").unwrap();
+
+        let mut moves = game.legal_moves("e7");
+        moves.sort();
+        assert_eq!(moves, ["e7e8b", "e7e8n", "e7e8q", "e7e8r"]);
+
+        assert!(WasmGame::from_fen("not a fen").is_none());
+    }
+}
@@ -0,0 +1,126 @@
+use crate::board::{Board, CastlingRights, Pos};
+use crate::pieces::{Color, Piece};
+
+/**
+ * The random keys that are xor-ed together to hash a position
+ */
+struct Keys {
+    pieces: [[[u64; 64]; 6]; 2],
+    black_to_move: u64,
+    castling: [u64; 4],
+    en_passant: [u64; 8],
+}
+
+/**
+ * A fixed seed, so that hashes are stable between runs
+ */
+const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+static KEYS: Keys = Keys::new();
+
+impl Keys {
+    const fn new() -> Self {
+        let mut state = SEED;
+        let mut keys = Keys {
+            pieces: [[[0; 64]; 6]; 2],
+            black_to_move: 0,
+            castling: [0; 4],
+            en_passant: [0; 8],
+        };
+
+        let mut color = 0;
+        while color < 2 {
+            let mut piece = 0;
+            while piece < 6 {
+                let mut square = 0;
+                while square < 64 {
+                    state = splitmix64(state);
+                    keys.pieces[color][piece][square] = state;
+                    square += 1;
+                }
+                piece += 1;
+            }
+            color += 1;
+        }
+
+        state = splitmix64(state);
+        keys.black_to_move = state;
+
+        let mut i = 0;
+        while i < 4 {
+            state = splitmix64(state);
+            keys.castling[i] = state;
+            i += 1;
+        }
+
+        let mut i = 0;
+        while i < 8 {
+            state = splitmix64(state);
+            keys.en_passant[i] = state;
+            i += 1;
+        }
+
+        keys
+    }
+}
+
+/**
+ * one step of the SplitMix64 generator
+ */
+const fn splitmix64(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Board {
+    /**
+     * Hashes the current position, this includes the piece placement, the side to
+     * move, the castling rights, and the en passant file (only if an en passant
+     * capture is actually possible)
+     *
+     * The halfmove clock and fullmove number are deliberately left out, so positions
+     * that only differ by their clocks hash the same. This is what repetition
+     * detection and transposition tables need, since the same position reached
+     * on a later move is still a repetition
+     */
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = 0;
+
+        for (Pos(x, y), piece, color) in self.board.iter() {
+            hash ^= KEYS.pieces[color as usize][piece as usize][y * 8 + x];
+        }
+
+        if self.turn == Color::Black {
+            hash ^= KEYS.black_to_move;
+        }
+
+        let castling = [
+            CastlingRights::WHITE_KINGSIDE,
+            CastlingRights::WHITE_QUEENSIDE,
+            CastlingRights::BLACK_KINGSIDE,
+            CastlingRights::BLACK_QUEENSIDE,
+        ];
+
+        for (&rights, &key) in castling.iter().zip(KEYS.castling.iter()) {
+            if self.castling.contains(rights) {
+                hash ^= key;
+            }
+        }
+
+        if let Some(ep) = self.en_passant {
+            let can_capture = self
+                .board
+                .iter()
+                .filter(|&(_, piece, color)| piece == Piece::Pawn && color == self.turn)
+                .any(|(pos, _, _)| self.en_passant_move(pos).is_some());
+
+            if can_capture {
+                hash ^= KEYS.en_passant[ep.0];
+            }
+        }
+
+        hash
+    }
+}